@@ -0,0 +1,443 @@
+//! Generates source code from the contract's document types: Rust structs for backends,
+//! TypeScript interfaces and a JavaScript SDK registration script for web apps.
+
+use crate::{DataType, DocumentType, Property};
+
+/// Rust keywords that can't be used as field names without the `r#` prefix
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "dyn", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "static", "struct",
+    "trait", "true", "type", "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try",
+];
+
+/// Keywords that can't be raw identifiers either, so they get a trailing underscore instead
+const NON_RAW_KEYWORDS: &[&str] = &["crate", "self", "super", "Self"];
+
+/// Splits camelCase, snake_case, kebab-case and space separated names into lowercase words
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut previous: Option<char> = None;
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+        } else {
+            let boundary = c.is_ascii_uppercase() && previous.map_or(false, |p| p.is_ascii_lowercase() || p.is_ascii_digit());
+            if boundary && !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            current.push(c.to_ascii_lowercase());
+        }
+        previous = Some(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// "userProfile" -> "UserProfile"
+pub fn to_pascal_case(name: &str) -> String {
+    let pascal: String = words(name)
+        .iter()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect();
+    match pascal.chars().next() {
+        Some(first) if first.is_ascii_digit() => format!("_{}", pascal),
+        Some(_) if pascal == "Self" => String::from("Self_"),
+        Some(_) => pascal,
+        None => String::from("Unnamed"),
+    }
+}
+
+/// "imageUrl" -> "image_url"
+pub fn to_snake_case(name: &str) -> String {
+    let snake = words(name).join("_");
+    match snake.chars().next() {
+        Some(first) if first.is_ascii_digit() => format!("_{}", snake),
+        Some(_) => snake,
+        None => String::from("unnamed"),
+    }
+}
+
+/// Formats text as `///` doc comment lines at the given indentation
+fn rust_doc_comment(text: &str, indent: &str) -> String {
+    text.lines()
+        .map(|line| match line.trim_end() {
+            "" => format!("{}///\n", indent),
+            line => format!("{}/// {}\n", indent, line),
+        })
+        .collect()
+}
+
+/// A Rust field name for a property, with the serde rename needed to keep the JSON name
+fn rust_field_name(name: &str) -> (String, Option<String>) {
+    let mut snake = to_snake_case(name);
+    if NON_RAW_KEYWORDS.contains(&snake.as_str()) {
+        snake.push('_');
+    }
+    let rename = if snake != name { Some(name.to_string()) } else { None };
+    if RUST_KEYWORDS.contains(&snake.as_str()) {
+        (format!("r#{}", snake), rename)
+    } else {
+        (snake, rename)
+    }
+}
+
+/// Returns the Rust type of a property, pushing the definitions of any nested structs it needs
+fn rust_type(property: &Property, struct_name: &str, structs: &mut Vec<String>) -> String {
+    match property.data_type {
+        DataType::String => String::from("String"),
+        DataType::Integer => {
+            let non_negative = property.minimum.as_ref().and_then(|n| n.as_f64()).map_or(false, |minimum| minimum >= 0.0)
+                || property.exclusive_minimum.as_ref().and_then(|n| n.as_f64()).map_or(false, |minimum| minimum >= -1.0);
+            if non_negative { String::from("u64") } else { String::from("i64") }
+        },
+        DataType::Number => String::from("f64"),
+        DataType::Boolean => String::from("bool"),
+        DataType::Array => {
+            if property.byte_array == Some(true) {
+                // serde only implements Serialize/Deserialize for arrays of up to 32 elements
+                match (property.min_items, property.max_items) {
+                    (Some(min), Some(max)) if min == max && max <= 32 => format!("[u8; {}]", max),
+                    _ => String::from("Vec<u8>"),
+                }
+            } else if let Some(items) = property.items.as_deref() {
+                format!("Vec<{}>", rust_type(items, &format!("{}Item", struct_name), structs))
+            } else {
+                String::from("Vec<serde_json::Value>")
+            }
+        },
+        DataType::Object => {
+            match property.properties.as_deref() {
+                Some(properties) if !properties.is_empty() => {
+                    rust_struct(struct_name, property.description.as_deref(), properties, &[], structs);
+                    struct_name.to_string()
+                },
+                _ => String::from("serde_json::Map<String, serde_json::Value>"),
+            }
+        },
+    }
+}
+
+/// Pushes a struct definition for a list of properties, followed by its nested structs.
+/// `system_fields` are required `$`-prefixed fields such as `$createdAt`.
+fn rust_struct(name: &str, doc: Option<&str>, properties: &[Property], system_fields: &[&str], structs: &mut Vec<String>) {
+    let mut nested = Vec::new();
+    let mut out = String::new();
+    if let Some(doc) = doc.filter(|doc| !doc.trim().is_empty()) {
+        out.push_str(&rust_doc_comment(doc, ""));
+    }
+    out.push_str("#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]\n");
+    out.push_str(&format!("pub struct {} {{\n", name));
+    for system_field in system_fields {
+        let (field_name, _) = rust_field_name(system_field.trim_start_matches('$'));
+        out.push_str("    /// Platform system field, in milliseconds since the Unix epoch\n");
+        out.push_str(&format!("    #[serde(rename = \"{}\")]\n", system_field));
+        out.push_str(&format!("    pub {}: u64,\n", field_name));
+    }
+    for property in properties {
+        let (field_name, rename) = rust_field_name(&property.name);
+        let nested_name = format!("{}{}", name, to_pascal_case(&property.name));
+        let mut field_type = rust_type(property, &nested_name, &mut nested);
+        if let Some(description) = property.description.as_deref().filter(|d| !d.trim().is_empty()) {
+            out.push_str(&rust_doc_comment(description, "    "));
+        }
+        let mut attributes = Vec::new();
+        if let Some(rename) = rename {
+            attributes.push(format!("rename = \"{}\"", rename));
+        }
+        if !property.required {
+            attributes.push(String::from("default, skip_serializing_if = \"Option::is_none\""));
+            field_type = format!("Option<{}>", field_type);
+        }
+        if !attributes.is_empty() {
+            out.push_str(&format!("    #[serde({})]\n", attributes.join(", ")));
+        }
+        out.push_str(&format!("    pub {}: {},\n", field_name, field_type));
+    }
+    out.push_str("}\n");
+    structs.push(out);
+    structs.extend(nested);
+}
+
+/// Generates a Rust module with one serde struct per document type
+pub fn rust_module(document_types: &[DocumentType]) -> String {
+    let mut structs = Vec::new();
+    for document_type in document_types {
+        let mut system_fields = Vec::new();
+        if document_type.created_at_required {
+            system_fields.push("$createdAt");
+        }
+        if document_type.updated_at_required {
+            system_fields.push("$updatedAt");
+        }
+        let doc = if document_type.comment.trim().is_empty() {
+            format!("A \"{}\" document", document_type.name)
+        } else {
+            document_type.comment.clone()
+        };
+        rust_struct(&to_pascal_case(&document_type.name), Some(&doc), &document_type.properties, &system_fields, &mut structs);
+    }
+    let mut out = String::from("//! Document types of the data contract, generated by Data Contract Creator.\n\nuse serde::{Deserialize, Serialize};\n");
+    for definition in structs {
+        out.push('\n');
+        out.push_str(&definition);
+    }
+    out
+}
+
+/// Formats text as a `/** ... */` JSDoc comment at the given indentation
+fn ts_doc_comment(text: &str, indent: &str) -> String {
+    let lines: Vec<&str> = text.lines().map(|line| line.trim_end()).collect();
+    match lines.as_slice() {
+        [line] => format!("{}/** {} */\n", indent, line.replace("*/", "* /")),
+        _ => {
+            let mut out = format!("{}/**\n", indent);
+            for line in lines {
+                out.push_str(format!("{} * {}", indent, line.replace("*/", "* /")).trim_end());
+                out.push('\n');
+            }
+            out.push_str(&format!("{} */\n", indent));
+            out
+        },
+    }
+}
+
+/// A TypeScript property key, quoted if it isn't a valid identifier
+fn ts_key(name: &str) -> String {
+    let is_identifier = name.chars().next().map_or(false, |c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if is_identifier { name.to_string() } else { serde_json::to_string(name).unwrap_or_default() }
+}
+
+/// Returns the TypeScript type of a property, pushing the definitions of any nested interfaces it needs
+fn ts_type(property: &Property, interface_name: &str, interfaces: &mut Vec<String>) -> String {
+    match property.data_type {
+        DataType::String => String::from("string"),
+        DataType::Integer | DataType::Number => String::from("number"),
+        DataType::Boolean => String::from("boolean"),
+        DataType::Array => {
+            match property.items.as_deref() {
+                _ if property.byte_array == Some(true) => String::from("Uint8Array"),
+                Some(items) => format!("{}[]", ts_type(items, &format!("{}Item", interface_name), interfaces)),
+                None => String::from("unknown[]"),
+            }
+        },
+        DataType::Object => {
+            match property.properties.as_deref() {
+                Some(properties) if !properties.is_empty() => {
+                    ts_interface(interface_name, property.description.as_deref(), properties, &[], interfaces);
+                    interface_name.to_string()
+                },
+                _ => String::from("Record<string, unknown>"),
+            }
+        },
+    }
+}
+
+/// Pushes an interface definition for a list of properties, followed by its nested interfaces
+fn ts_interface(name: &str, doc: Option<&str>, properties: &[Property], system_fields: &[&str], interfaces: &mut Vec<String>) {
+    let mut nested = Vec::new();
+    let mut out = String::new();
+    if let Some(doc) = doc.filter(|doc| !doc.trim().is_empty()) {
+        out.push_str(&ts_doc_comment(doc, ""));
+    }
+    out.push_str(&format!("export interface {} {{\n", name));
+    for system_field in system_fields {
+        out.push_str("  /** Platform system field, in milliseconds since the Unix epoch */\n");
+        out.push_str(&format!("  {}: number;\n", system_field));
+    }
+    for property in properties {
+        let nested_name = format!("{}{}", name, to_pascal_case(&property.name));
+        let field_type = ts_type(property, &nested_name, &mut nested);
+        if let Some(description) = property.description.as_deref().filter(|d| !d.trim().is_empty()) {
+            out.push_str(&ts_doc_comment(description, "  "));
+        }
+        let optional = if property.required { "" } else { "?" };
+        out.push_str(&format!("  {}{}: {};\n", ts_key(&property.name), optional, field_type));
+    }
+    out.push_str("}\n");
+    interfaces.push(out);
+    interfaces.extend(nested);
+}
+
+/// Generates TypeScript interfaces, one per document type
+pub fn typescript_types(document_types: &[DocumentType]) -> String {
+    let mut interfaces = Vec::new();
+    for document_type in document_types {
+        let mut system_fields = Vec::new();
+        if document_type.created_at_required {
+            system_fields.push("$createdAt");
+        }
+        if document_type.updated_at_required {
+            system_fields.push("$updatedAt");
+        }
+        let doc = if document_type.comment.trim().is_empty() {
+            format!("A \"{}\" document", document_type.name)
+        } else {
+            document_type.comment.clone()
+        };
+        ts_interface(&to_pascal_case(&document_type.name), Some(&doc), &document_type.properties, &system_fields, &mut interfaces);
+    }
+    let mut out = String::from("// Document types of the data contract, generated by Data Contract Creator.\n");
+    for definition in interfaces {
+        out.push('\n');
+        out.push_str(&definition);
+    }
+    out
+}
+
+/// Generates a Node.js script that registers the contract with the JavaScript SDK.
+/// `contract_json` is the pretty-printed contract.
+pub fn registration_script(contract_json: &str) -> String {
+    let documents = contract_json.replace('\n', "\n  ");
+    format!(r#"// Registers the data contract on Hellar Platform testnet, generated by Data Contract Creator.
+// Requires the JavaScript SDK: npm install hellar
+// Fill in a funded wallet mnemonic and an identity id owned by it, then run it with node
+const Hellar = require('hellar');
+
+const clientOpts = {{
+  network: 'testnet',
+  wallet: {{
+    mnemonic: 'a Hellar wallet mnemonic with funds goes here',
+    unsafeOptions: {{
+      skipSynchronizationBeforeHeight: 875000,
+    }},
+  }},
+}};
+const client = new Hellar.Client(clientOpts);
+
+const registerContract = async () => {{
+  const {{ platform }} = client;
+  const identity = await platform.identities.get('an identity ID goes here');
+
+  const contractDocuments = {documents};
+
+  const contract = await platform.contracts.create(contractDocuments, identity);
+  console.dir({{ contract: contract.toJSON() }});
+
+  // Make sure contract passes validation checks
+  const validationResult = await platform.dpp.dataContract.validate(contract);
+  if (!validationResult.isValid()) {{
+    console.error(validationResult);
+    throw validationResult.errors[0];
+  }}
+
+  // Sign and submit the data contract
+  await platform.contracts.publish(contract, identity);
+  return contract;
+}};
+
+registerContract()
+  .then((d) => console.log('Contract registered:\n', d.toJSON()))
+  .catch((e) => console.error('Something went wrong:\n', e))
+  .finally(() => client.disconnect());
+"#)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keywords_that_cant_be_raw_get_a_suffix() {
+        assert_eq!(rust_field_name("crate"), (String::from("crate_"), Some(String::from("crate"))));
+        assert_eq!(rust_field_name("self"), (String::from("self_"), Some(String::from("self"))));
+        assert_eq!(rust_field_name("Self"), (String::from("self_"), Some(String::from("Self"))));
+        assert_eq!(rust_field_name("super"), (String::from("super_"), Some(String::from("super"))));
+    }
+
+    #[test]
+    fn other_keywords_become_raw_identifiers() {
+        assert_eq!(rust_field_name("type"), (String::from("r#type"), None));
+        assert_eq!(rust_field_name("matchType"), (String::from("match_type"), Some(String::from("matchType"))));
+    }
+
+    #[test]
+    fn leading_digits_are_prefixed() {
+        assert_eq!(rust_field_name("1abc"), (String::from("_1abc"), Some(String::from("1abc"))));
+        assert_eq!(rust_field_name("2ndPlace"), (String::from("_2nd_place"), Some(String::from("2ndPlace"))));
+    }
+
+    #[test]
+    fn self_is_not_a_struct_name() {
+        assert_eq!(to_pascal_case("self"), "Self_");
+        assert_eq!(to_pascal_case("selfie"), "Selfie");
+    }
+
+    #[test]
+    fn generated_fields_keep_their_json_names() {
+        let document_type = DocumentType {
+            name: String::from("note"),
+            properties: ["crate", "self", "1abc"].iter().map(|name| Property {
+                name: name.to_string(),
+                data_type: DataType::String,
+                required: true,
+                ..Default::default()
+            }).collect(),
+            ..Default::default()
+        };
+        let module = rust_module(&[document_type]);
+        assert!(module.contains("    #[serde(rename = \"crate\")]\n    pub crate_: String,"));
+        assert!(module.contains("    #[serde(rename = \"self\")]\n    pub self_: String,"));
+        assert!(module.contains("    #[serde(rename = \"1abc\")]\n    pub _1abc: String,"));
+    }
+
+    #[test]
+    fn quotes_typescript_keys_as_json_strings() {
+        assert_eq!(ts_key("name"), "name");
+        assert_eq!(ts_key("$id"), "$id");
+        assert_eq!(ts_key("first-name"), "\"first-name\"");
+        assert_eq!(ts_key("say \"hi\""), "\"say \\\"hi\\\"\"");
+        // Rust's debug escaping would write \u{e9}, which isn't valid JavaScript
+        assert_eq!(ts_key("café"), "\"café\"");
+        assert_eq!(ts_key("tab\there"), "\"tab\\there\"");
+    }
+
+    #[test]
+    fn generates_typescript_types() {
+        let author = Property { name: String::from("display name"), data_type: DataType::String, required: true, ..Default::default() };
+        let document_type = DocumentType {
+            name: String::from("note"),
+            comment: String::from("A note, ending with */"),
+            created_at_required: true,
+            properties: vec![
+                Property { name: String::from("message"), data_type: DataType::String, required: true, description: Some(String::from("The text")), ..Default::default() },
+                Property { name: String::from("tags"), data_type: DataType::Array, items: Some(Box::new(Property { data_type: DataType::String, ..Default::default() })), ..Default::default() },
+                Property { name: String::from("author"), data_type: DataType::Object, properties: Some(Box::new(vec![author])), ..Default::default() },
+            ],
+            ..Default::default()
+        };
+        let types = typescript_types(&[document_type]);
+        assert!(types.contains("/** A note, ending with * / */\nexport interface Note {\n"));
+        assert!(types.contains("  $createdAt: number;\n"));
+        assert!(types.contains("  /** The text */\n  message: string;\n"));
+        assert!(types.contains("  tags?: string[];\n"));
+        assert!(types.contains("  author?: NoteAuthor;\n}\n"));
+        assert!(types.contains("export interface NoteAuthor {\n  \"display name\": string;\n}\n"));
+    }
+
+    #[test]
+    fn embeds_the_contract_in_the_registration_script() {
+        let contract = serde_json::to_string_pretty(&serde_json::json!({
+            "note": {"type": "object", "properties": {"message": {"type": "string", "description": "It's \"quoted\""}}}
+        })).unwrap();
+        let script = registration_script(&contract);
+        let start = script.find("const contractDocuments = ").unwrap() + "const contractDocuments = ".len();
+        let end = start + script[start..].find(";\n").unwrap();
+        let embedded: serde_json::Value = serde_json::from_str(&script[start..end]).unwrap();
+        assert_eq!(embedded, serde_json::from_str::<serde_json::Value>(&contract).unwrap());
+        assert!(script.contains("  const contractDocuments = {\n    \"note\": {"));
+        assert!(script.contains("await platform.contracts.publish(contract, identity);"));
+    }
+}
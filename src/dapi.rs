@@ -0,0 +1,410 @@
+//! Minimal gRPC-web client for the DAPI Platform service, used to broadcast state transitions,
+//! wait for their results and fetch registered contracts. Messages are encoded by hand,
+//! since only a few bytes fields are needed.
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use sha2::{Digest, Sha256};
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Request, RequestInit, RequestMode, Response};
+
+/// Fully qualified name of the DAPI Platform gRPC service
+const PLATFORM_SERVICE: &str = "org.hellar.platform.dapi.v0.Platform";
+
+/// gRPC status code of an invalid argument, used by DAPI for invalid state transitions
+const INVALID_ARGUMENT: u32 = 3;
+
+/// Why a DAPI call failed
+#[derive(Debug)]
+pub enum DapiError {
+    /// Platform rejected the state transition. `data` is the serialized consensus error, if sent.
+    Rejected { code: u32, message: String, data: Option<Vec<u8>> },
+    Other(anyhow::Error),
+}
+
+impl From<anyhow::Error> for DapiError {
+    fn from(err: anyhow::Error) -> Self {
+        DapiError::Other(err)
+    }
+}
+
+impl std::fmt::Display for DapiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DapiError::Rejected { code, message, .. } => write!(f, "Rejected with code {}: {}", code, message),
+            DapiError::Other(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+/// A decoded protobuf field value. Fixed-width fields aren't used by the messages here.
+enum Field {
+    Varint(u64),
+    Bytes(Vec<u8>),
+}
+
+fn encode_varint(mut value: u64, out: &mut Vec<u8>) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn encode_bytes_field(number: u32, bytes: &[u8], out: &mut Vec<u8>) {
+    encode_varint(((number << 3) | 2) as u64, out);
+    encode_varint(bytes.len() as u64, out);
+    out.extend_from_slice(bytes);
+}
+
+fn encode_bool_field(number: u32, value: bool, out: &mut Vec<u8>) {
+    encode_varint((number << 3) as u64, out);
+    out.push(value as u8);
+}
+
+fn decode_varint(bytes: &[u8], pos: &mut usize) -> Result<u64, anyhow::Error> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes.get(*pos).ok_or_else(|| anyhow::anyhow!("Truncated protobuf varint"))?;
+        *pos += 1;
+        value |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(anyhow::anyhow!("Invalid protobuf varint"))
+}
+
+/// Reads the next `len` bytes of a protobuf message
+fn take<'a>(bytes: &'a [u8], pos: &mut usize, len: u64) -> Result<&'a [u8], anyhow::Error> {
+    let end = usize::try_from(len).ok()
+        .and_then(|len| pos.checked_add(len))
+        .filter(|end| *end <= bytes.len())
+        .ok_or_else(|| anyhow::anyhow!("Truncated protobuf field"))?;
+    let value = &bytes[*pos..end];
+    *pos = end;
+    Ok(value)
+}
+
+/// Decodes the top-level fields of a protobuf message
+fn decode_message(bytes: &[u8]) -> Result<Vec<(u32, Field)>, anyhow::Error> {
+    let mut fields = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let key = decode_varint(bytes, &mut pos)?;
+        let number = (key >> 3) as u32;
+        match key & 7 {
+            0 => fields.push((number, Field::Varint(decode_varint(bytes, &mut pos)?))),
+            1 => {
+                take(bytes, &mut pos, 8)?;
+            },
+            2 => {
+                let len = decode_varint(bytes, &mut pos)?;
+                fields.push((number, Field::Bytes(take(bytes, &mut pos, len)?.to_vec())));
+            },
+            5 => {
+                take(bytes, &mut pos, 4)?;
+            },
+            wire_type => return Err(anyhow::anyhow!("Unsupported protobuf wire type {}", wire_type)),
+        }
+    }
+    Ok(fields)
+}
+
+fn bytes_field(fields: &[(u32, Field)], number: u32) -> Option<&[u8]> {
+    fields.iter().find_map(|(n, field)| match field {
+        Field::Bytes(bytes) if *n == number => Some(bytes.as_slice()),
+        _ => None,
+    })
+}
+
+fn varint_field(fields: &[(u32, Field)], number: u32) -> Option<u64> {
+    fields.iter().find_map(|(n, field)| match field {
+        Field::Varint(value) if *n == number => Some(*value),
+        _ => None,
+    })
+}
+
+/// Decodes the `%XX` escapes of a grpc-message
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            if let Some(byte) = std::str::from_utf8(&bytes[i + 1..i + 3]).ok().and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Turns gRPC status metadata into an error, if the status isn't OK
+fn status_error(metadata: &[(String, String)]) -> Option<DapiError> {
+    let get = |key: &str| metadata.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+    let code: u32 = get("grpc-status")?.trim().parse().ok()?;
+    if code == 0 {
+        return None;
+    }
+    let message = percent_decode(get("grpc-message").unwrap_or_default());
+    // DAPI attaches the serialized consensus error as binary metadata
+    let data = metadata.iter()
+        .find(|(k, _)| k.ends_with("-serialized-consensus-error-bin"))
+        .and_then(|(_, v)| BASE64.decode(v.trim()).ok());
+    if code == INVALID_ARGUMENT || data.is_some() {
+        Some(DapiError::Rejected { code, message, data })
+    } else {
+        Some(DapiError::Other(anyhow::anyhow!("DAPI returned gRPC status {}: {}", code, message)))
+    }
+}
+
+/// Parses `key: value` lines of a gRPC-web trailer frame
+fn parse_trailers(bytes: &[u8]) -> Vec<(String, String)> {
+    String::from_utf8_lossy(bytes)
+        .split("\r\n")
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect()
+}
+
+/// Calls a unary method of the Platform service and returns the response message
+async fn call(endpoint: &str, method: &str, message: &[u8]) -> Result<Vec<u8>, DapiError> {
+    let endpoint = endpoint.trim().trim_end_matches('/');
+    if endpoint.is_empty() {
+        return Err(anyhow::anyhow!("Enter the DAPI endpoint first.").into());
+    }
+
+    // gRPC-web frame: a zero flag byte, the big-endian message length, then the message
+    let mut body = vec![0u8];
+    body.extend_from_slice(&(message.len() as u32).to_be_bytes());
+    body.extend_from_slice(message);
+
+    let mut opts = RequestInit::new();
+    let headers = web_sys::Headers::new().unwrap();
+    headers.append("Content-Type", "application/grpc-web+proto").unwrap();
+    headers.append("X-Grpc-Web", "1").unwrap();
+    opts.method("POST");
+    opts.headers(&headers);
+    opts.body(Some(&js_sys::Uint8Array::from(body.as_slice()).into()));
+    opts.mode(RequestMode::Cors);
+
+    let url = format!("{}/{}/{}", endpoint, PLATFORM_SERVICE, method);
+    let request = Request::new_with_str_and_init(&url, &opts)
+        .map_err(|e| anyhow::anyhow!("Failed to create request: {:?}", e))?;
+    let window = web_sys::window().ok_or_else(|| anyhow::anyhow!("Failed to obtain window object"))?;
+    let response: Response = JsFuture::from(window.fetch_with_request(&request))
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to reach {}: {}", endpoint, e.as_string().unwrap_or_else(|| String::from("network error"))))?
+        .dyn_into()
+        .map_err(|_| anyhow::anyhow!("Unexpected fetch result"))?;
+    if !response.ok() {
+        return Err(anyhow::anyhow!("DAPI returned HTTP {}", response.status()).into());
+    }
+
+    // A trailers-only response carries the status in the HTTP headers
+    let header_metadata: Vec<(String, String)> = ["grpc-status", "grpc-message"].iter()
+        .filter_map(|key| response.headers().get(key).ok().flatten().map(|value| (key.to_string(), value)))
+        .collect();
+    if let Some(err) = status_error(&header_metadata) {
+        return Err(err);
+    }
+
+    let buffer = JsFuture::from(response.array_buffer().map_err(|_| anyhow::anyhow!("Failed to read the response"))?)
+        .await
+        .map_err(|_| anyhow::anyhow!("Failed to read the response"))?;
+    let bytes = js_sys::Uint8Array::new(&buffer).to_vec();
+
+    let (message, trailers) = parse_frames(&bytes)?;
+    if let Some(err) = status_error(&trailers) {
+        return Err(err);
+    }
+    Ok(message.unwrap_or_default())
+}
+
+/// Splits a gRPC-web response body into its first message and its trailers
+fn parse_frames(bytes: &[u8]) -> Result<(Option<Vec<u8>>, Vec<(String, String)>), anyhow::Error> {
+    let mut message = None;
+    let mut trailers = Vec::new();
+    let mut pos = 0;
+    while bytes.len() - pos >= 5 {
+        let flag = bytes[pos];
+        let len = u32::from_be_bytes([bytes[pos + 1], bytes[pos + 2], bytes[pos + 3], bytes[pos + 4]]) as usize;
+        let end = (pos + 5).checked_add(len)
+            .filter(|end| *end <= bytes.len())
+            .ok_or_else(|| anyhow::anyhow!("Truncated gRPC-web response"))?;
+        let frame = &bytes[pos + 5..end];
+        if flag & 0x80 != 0 {
+            trailers.extend(parse_trailers(frame));
+        } else if message.is_none() {
+            message = Some(frame.to_vec());
+        }
+        pos = end;
+    }
+    Ok((message, trailers))
+}
+
+/// Hash that identifies a state transition: SHA-256 of its signed serialization
+pub fn state_transition_hash(bytes: &[u8]) -> [u8; 32] {
+    Sha256::digest(bytes).into()
+}
+
+/// Submits a signed, serialized state transition
+pub async fn broadcast_state_transition(endpoint: &str, state_transition: &[u8]) -> Result<(), DapiError> {
+    let mut request = Vec::new();
+    encode_bytes_field(1, state_transition, &mut request);
+    call(endpoint, "broadcastStateTransition", &request).await.map(|_| ())
+}
+
+/// Waits until a broadcast state transition is executed, failing if Platform rejects it
+pub async fn wait_for_state_transition_result(endpoint: &str, hash: &[u8; 32]) -> Result<(), DapiError> {
+    let mut request = Vec::new();
+    encode_bytes_field(1, hash, &mut request);
+    encode_bool_field(2, false, &mut request);
+    let response = call(endpoint, "waitForStateTransitionResult", &request).await?;
+    let fields = decode_message(&response)?;
+    match bytes_field(&fields, 1) {
+        Some(error) => {
+            // StateTransitionBroadcastError { uint32 code = 1; string message = 2; bytes data = 3; }
+            let error = decode_message(error)?;
+            Err(DapiError::Rejected {
+                code: varint_field(&error, 1).unwrap_or_default() as u32,
+                message: bytes_field(&error, 2).map(|m| String::from_utf8_lossy(m).into_owned()).unwrap_or_default(),
+                data: bytes_field(&error, 3).filter(|data| !data.is_empty()).map(|data| data.to_vec()),
+            })
+        },
+        None => Ok(()),
+    }
+}
+
+/// Fetches a registered data contract, serialized with Platform's encoding
+pub async fn get_data_contract(endpoint: &str, id: &[u8]) -> Result<Vec<u8>, DapiError> {
+    let mut request = Vec::new();
+    encode_bytes_field(1, id, &mut request);
+    encode_bool_field(2, false, &mut request);
+    let response = call(endpoint, "getDataContract", &request).await?;
+    let fields = decode_message(&response)?;
+    match bytes_field(&fields, 1) {
+        Some(data_contract) if !data_contract.is_empty() => Ok(data_contract.to_vec()),
+        _ => Err(anyhow::anyhow!("No data contract found with this id.").into()),
+    }
+}
+
+/// Broadcasts a signed state transition and waits for its result.
+/// Like the JS SDK, the wait starts before the broadcast, so a result that arrives quickly isn't missed.
+pub async fn submit_state_transition(endpoint: &str, state_transition: &[u8]) -> Result<(), DapiError> {
+    use std::{future::Future, task::Poll};
+    let hash = state_transition_hash(state_transition);
+    let mut wait = Box::pin(wait_for_state_transition_result(endpoint, &hash));
+    let mut broadcast = Box::pin(broadcast_state_transition(endpoint, state_transition));
+    let mut broadcasted = false;
+    let mut result = None;
+    std::future::poll_fn(|cx| {
+        // The wait is polled first, so its request is sent before the broadcast
+        if result.is_none() {
+            if let Poll::Ready(wait_result) = wait.as_mut().poll(cx) {
+                result = Some(wait_result);
+            }
+        }
+        if !broadcasted {
+            match broadcast.as_mut().poll(cx) {
+                Poll::Ready(Err(err)) => return Poll::Ready(Err(err)),
+                Poll::Ready(Ok(())) => broadcasted = true,
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+        match result.take() {
+            Some(wait_result) => Poll::Ready(wait_result),
+            None => Poll::Pending,
+        }
+    }).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_varints() {
+        let mut out = Vec::new();
+        encode_varint(1, &mut out);
+        encode_varint(300, &mut out);
+        encode_varint(u64::MAX, &mut out);
+        assert_eq!(out, [0x01, 0xac, 0x02, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]);
+    }
+
+    #[test]
+    fn decodes_varints() {
+        let bytes = [0x01, 0xac, 0x02, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01];
+        let mut pos = 0;
+        assert_eq!(decode_varint(&bytes, &mut pos).unwrap(), 1);
+        assert_eq!(decode_varint(&bytes, &mut pos).unwrap(), 300);
+        assert_eq!(decode_varint(&bytes, &mut pos).unwrap(), u64::MAX);
+        assert_eq!(pos, bytes.len());
+        assert!(decode_varint(&[0xac], &mut 0).is_err());
+        assert!(decode_varint(&[0x80; 11], &mut 0).is_err());
+    }
+
+    #[test]
+    fn decodes_messages() {
+        // field 1: bytes "ab", field 2: varint 150, field 3: fixed64, field 4: fixed32
+        let bytes = [
+            0x0a, 0x02, b'a', b'b',
+            0x10, 0x96, 0x01,
+            0x19, 1, 2, 3, 4, 5, 6, 7, 8,
+            0x25, 1, 2, 3, 4,
+        ];
+        let fields = decode_message(&bytes).unwrap();
+        assert_eq!(fields.len(), 2);
+        assert_eq!(bytes_field(&fields, 1), Some(&b"ab"[..]));
+        assert_eq!(varint_field(&fields, 2), Some(150));
+    }
+
+    #[test]
+    fn rejects_truncated_messages() {
+        for bytes in [
+            &[0x0a, 0x03, b'a', b'b'][..],
+            &[0x19, 1, 2, 3][..],
+            &[0x25, 1, 2][..],
+            // length of u64::MAX
+            &[0x0a, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01, b'a'][..],
+        ] {
+            let err = decode_message(bytes).err().unwrap();
+            assert_eq!(err.to_string(), "Truncated protobuf field");
+        }
+        assert!(decode_message(&[0x0b]).is_err());
+    }
+
+    #[test]
+    fn decodes_percent_escapes() {
+        assert_eq!(percent_decode("invalid%20state%3A%20x"), "invalid state: x");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz%4"), "%zz%4");
+        assert_eq!(percent_decode("caf%C3%A9"), "café");
+    }
+
+    #[test]
+    fn parses_trailers() {
+        let trailers = parse_trailers(b"grpc-status: 3\r\nGrpc-Message: bad%20input\r\nno colon\r\n");
+        assert_eq!(trailers, [
+            (String::from("grpc-status"), String::from("3")),
+            (String::from("grpc-message"), String::from("bad%20input")),
+        ]);
+        assert!(matches!(status_error(&trailers), Some(DapiError::Rejected { code: 3, ref message, data: None }) if message == "bad input"));
+    }
+
+    #[test]
+    fn parses_frames() {
+        let mut bytes = vec![0x00, 0, 0, 0, 2, 0x08, 0x01];
+        bytes.extend_from_slice(&[0x80, 0, 0, 0, 16]);
+        bytes.extend_from_slice(b"grpc-status: 0\r\n");
+        let (message, trailers) = parse_frames(&bytes).unwrap();
+        assert_eq!(message, Some(vec![0x08, 0x01]));
+        assert_eq!(trailers, [(String::from("grpc-status"), String::from("0"))]);
+        assert!(parse_frames(&[0x00, 0xff, 0xff, 0xff, 0xff, 0x01]).is_err());
+    }
+}
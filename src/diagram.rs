@@ -0,0 +1,291 @@
+//! Entity-relationship diagram of the document types, as Mermaid source and as an in-app SVG.
+//!
+//! References between document types aren't declared in contracts, so they are inferred
+//! from identifier properties: 32-byte byte arrays named after a document type plus `Id`,
+//! e.g. `listingId` referencing the `listing` document type.
+
+use yew::{html, Html};
+use crate::{codegen::to_pascal_case, DataType, DocumentType, Property};
+
+/// An identifier property of one document type pointing at another
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reference {
+    /// Index of the referencing document type
+    pub from: usize,
+    /// Dotted path of the identifier property, with `[]` after arrays whose items hold it
+    pub property: String,
+    /// Index of the referenced document type
+    pub to: usize,
+}
+
+/// Whether a property holds a 32-byte identifier
+fn is_identifier(property: &Property) -> bool {
+    property.data_type == DataType::Array
+        && property.byte_array == Some(true)
+        && (property.min_items == Some(32) && property.max_items == Some(32)
+            || property.content_media_type.as_deref().map_or(false, |t| t.ends_with(".identifier")))
+}
+
+/// The document type an identifier property is named after, e.g. `listing` for `listingId`
+/// or `parentListingId`. The longest matching document type name wins.
+fn referenced_type(property_name: &str, document_types: &[DocumentType]) -> Option<usize> {
+    let stem = property_name.strip_suffix("Id")
+        .or_else(|| property_name.strip_suffix("_id"))
+        .or_else(|| property_name.strip_suffix("ID"))?;
+    let stem = to_pascal_case(stem);
+    document_types.iter()
+        .enumerate()
+        .map(|(index, document_type)| (index, to_pascal_case(&document_type.name)))
+        .filter(|(_, name)| !name.is_empty() && stem.ends_with(name.as_str()))
+        .max_by_key(|(_, name)| name.len())
+        .map(|(index, _)| index)
+}
+
+fn collect_references(properties: &[Property], prefix: &str, from: usize, document_types: &[DocumentType], references: &mut Vec<Reference>) {
+    for property in properties {
+        let path = format!("{}{}", prefix, property.name);
+        if is_identifier(property) {
+            if let Some(to) = referenced_type(&property.name, document_types) {
+                references.push(Reference { from, property: path.clone(), to });
+            }
+        }
+        if let Some(nested) = property.properties.as_deref() {
+            collect_references(nested, &format!("{}.", path), from, document_types, references);
+        }
+        if let Some(fields) = property.items.as_deref().and_then(|items| items.properties.as_deref()) {
+            collect_references(fields, &format!("{}[].", path), from, document_types, references);
+        }
+    }
+}
+
+/// Infers the references between document types
+pub fn references(document_types: &[DocumentType]) -> Vec<Reference> {
+    let mut references = Vec::new();
+    for (from, document_type) in document_types.iter().enumerate() {
+        collect_references(&document_type.properties, "", from, document_types, &mut references);
+    }
+    references
+}
+
+/// Short type name shown next to each property
+fn type_label(property: &Property) -> &'static str {
+    match property.data_type {
+        DataType::String => "string",
+        DataType::Integer => "integer",
+        DataType::Number => "number",
+        DataType::Boolean => "boolean",
+        DataType::Array if is_identifier(property) => "identifier",
+        DataType::Array if property.byte_array == Some(true) => "bytes",
+        DataType::Array => "array",
+        DataType::Object => "object",
+    }
+}
+
+/// Flattens properties into (dotted path, type, required) rows, including fields of object array items
+fn rows(properties: &[Property], prefix: &str, out: &mut Vec<(String, &'static str, bool)>) {
+    for property in properties {
+        let path = format!("{}{}", prefix, property.name);
+        out.push((path.clone(), type_label(property), property.required));
+        if let Some(nested) = property.properties.as_deref() {
+            rows(nested, &format!("{}.", path), out);
+        }
+        if let Some(fields) = property.items.as_deref().and_then(|items| items.properties.as_deref()) {
+            rows(fields, &format!("{}[].", path), out);
+        }
+    }
+}
+
+fn document_rows(document_type: &DocumentType) -> Vec<(String, &'static str, bool)> {
+    let mut out = Vec::new();
+    if document_type.created_at_required {
+        out.push((String::from("$createdAt"), "integer", true));
+    }
+    if document_type.updated_at_required {
+        out.push((String::from("$updatedAt"), "integer", true));
+    }
+    rows(&document_type.properties, "", &mut out);
+    out
+}
+
+/// Replaces characters Mermaid doesn't accept in entity and attribute names, which also can't start with a digit
+fn mermaid_name(name: &str) -> String {
+    let name: String = name.chars().map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' }).collect();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) { format!("_{}", name) } else { name }
+}
+
+/// Generates a Mermaid `erDiagram` of the document types and their references
+pub fn mermaid(document_types: &[DocumentType]) -> String {
+    let mut out = String::from("%% Document types of the data contract, generated by Data Contract Creator.\nerDiagram\n");
+    for document_type in document_types {
+        out.push_str(&format!("    {} {{\n", mermaid_name(&document_type.name)));
+        for (path, type_label, required) in document_rows(document_type) {
+            let key = if required { " \"required\"" } else { "" };
+            out.push_str(&format!("        {} {}{}\n", type_label, mermaid_name(&path), key));
+        }
+        out.push_str("    }\n");
+    }
+    for reference in references(document_types) {
+        out.push_str(&format!(
+            "    {} ||--o{{ {} : \"{}\"\n",
+            mermaid_name(&document_types[reference.to].name),
+            mermaid_name(&document_types[reference.from].name),
+            reference.property,
+        ));
+    }
+    out
+}
+
+const BOX_WIDTH: i32 = 220;
+const HEADER_HEIGHT: i32 = 26;
+const ROW_HEIGHT: i32 = 18;
+const GAP: i32 = 60;
+
+/// Position and size of a document type's box
+struct Node {
+    x: i32,
+    y: i32,
+    height: i32,
+}
+
+impl Node {
+    fn center(&self) -> (i32, i32) {
+        (self.x + BOX_WIDTH / 2, self.y + self.height / 2)
+    }
+}
+
+/// Lays the boxes out in a roughly square grid, each grid row as tall as its tallest box
+fn layout(document_types: &[DocumentType]) -> Vec<Node> {
+    let columns = (document_types.len() as f64).sqrt().ceil().max(1.0) as usize;
+    let mut nodes = Vec::new();
+    let mut y = GAP / 2;
+    for row in document_types.chunks(columns) {
+        let heights: Vec<i32> = row.iter()
+            .map(|document_type| HEADER_HEIGHT + ROW_HEIGHT * document_rows(document_type).len().max(1) as i32 + 8)
+            .collect();
+        for (column, height) in heights.iter().enumerate() {
+            nodes.push(Node { x: GAP / 2 + column as i32 * (BOX_WIDTH + GAP), y, height: *height });
+        }
+        y += heights.iter().max().copied().unwrap_or_default() + GAP;
+    }
+    nodes
+}
+
+/// Renders the diagram as SVG, with reference lines drawn beneath the boxes
+pub fn svg(document_types: &[DocumentType]) -> Html {
+    if document_types.is_empty() {
+        return html! {<p>{"No document types yet."}</p>};
+    }
+    let nodes = layout(document_types);
+    let references = references(document_types);
+    let width = nodes.iter().map(|node| node.x + BOX_WIDTH).max().unwrap_or_default() + GAP / 2;
+    let height = nodes.iter().map(|node| node.y + node.height).max().unwrap_or_default() + GAP / 2;
+    let lines = references.iter().map(|reference| {
+        let (x1, y1) = nodes[reference.from].center();
+        let (x2, y2) = nodes[reference.to].center();
+        if reference.from == reference.to {
+            // Loop on the right edge of the box
+            let node = &nodes[reference.from];
+            let right = node.x + BOX_WIDTH;
+            let path = format!("M {} {} C {} {}, {} {}, {} {}", right, y1 - 10, right + 40, y1 - 30, right + 40, y1 + 30, right, y1 + 10);
+            return html! {
+                <g>
+                    <path class="diagram-reference" d={path} />
+                    <text class="diagram-label" x={(right + 4).to_string()} y={(y1 - 26).to_string()}>{reference.property.clone()}</text>
+                </g>
+            };
+        }
+        html! {
+            <g>
+                <line class="diagram-reference" x1={x1.to_string()} y1={y1.to_string()} x2={x2.to_string()} y2={y2.to_string()} />
+                <circle class="diagram-target" cx={x2.to_string()} cy={y2.to_string()} r="4" />
+                <text class="diagram-label" x={((x1 + x2) / 2).to_string()} y={((y1 + y2) / 2 - 4).to_string()}>{reference.property.clone()}</text>
+            </g>
+        }
+    });
+    let boxes = document_types.iter().zip(nodes.iter()).enumerate().map(|(index, (document_type, node))| {
+        let rows = document_rows(document_type);
+        html! {
+            <g>
+                <title>{document_type.comment.clone()}</title>
+                <rect class="diagram-box" x={node.x.to_string()} y={node.y.to_string()} width={BOX_WIDTH.to_string()} height={node.height.to_string()} />
+                <rect class="diagram-header" x={node.x.to_string()} y={node.y.to_string()} width={BOX_WIDTH.to_string()} height={HEADER_HEIGHT.to_string()} />
+                <text class="diagram-name" x={(node.x + 8).to_string()} y={(node.y + 18).to_string()}>{document_type.name.clone()}</text>
+                {for rows.into_iter().enumerate().map(|(row, (path, type_label, required))| {
+                    let y = node.y + HEADER_HEIGHT + ROW_HEIGHT * (row as i32 + 1);
+                    let target = references.iter()
+                        .find(|reference| reference.from == index && reference.property == path)
+                        .map(|reference| format!(" → {}", document_types[reference.to].name))
+                        .unwrap_or_default();
+                    let class = if required { "diagram-property required" } else { "diagram-property" };
+                    html! {
+                        <text class={class} x={(node.x + 8).to_string()} y={y.to_string()}>
+                            {format!("{}: {}{}", path, type_label, target)}
+                        </text>
+                    }
+                })}
+            </g>
+        }
+    });
+    html! {
+        <svg class="diagram" width={width.to_string()} height={height.to_string()} viewBox={format!("0 0 {} {}", width, height)}>
+            {for lines}
+            {for boxes}
+        </svg>
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identifier(name: &str) -> Property {
+        Property { name: name.to_string(), data_type: DataType::Array, byte_array: Some(true), min_items: Some(32), max_items: Some(32), ..Default::default() }
+    }
+
+    fn document_type(name: &str, properties: Vec<Property>) -> DocumentType {
+        DocumentType { name: name.to_string(), properties, ..Default::default() }
+    }
+
+    #[test]
+    fn names_references_after_document_types() {
+        let document_types = [document_type("listing", vec![]), document_type("featuredListing", vec![])];
+        assert_eq!(referenced_type("listingId", &document_types), Some(0));
+        assert_eq!(referenced_type("listing_id", &document_types), Some(0));
+        assert_eq!(referenced_type("listingID", &document_types), Some(0));
+        assert_eq!(referenced_type("parentListingId", &document_types), Some(0));
+        // The longest matching name wins over a shorter one it ends with
+        assert_eq!(referenced_type("featuredListingId", &document_types), Some(1));
+        assert_eq!(referenced_type("listing", &document_types), None);
+        assert_eq!(referenced_type("userId", &document_types), None);
+    }
+
+    #[test]
+    fn finds_references() {
+        let offers = Property {
+            name: String::from("offers"),
+            data_type: DataType::Array,
+            items: Some(Box::new(Property { data_type: DataType::Object, properties: Some(Box::new(vec![identifier("bidId")])), ..Default::default() })),
+            ..Default::default()
+        };
+        let not_an_identifier = Property { name: String::from("bidId"), data_type: DataType::String, ..Default::default() };
+        let document_types = [
+            document_type("listing", vec![identifier("parentListingId"), offers]),
+            document_type("bid", vec![identifier("listingId"), not_an_identifier]),
+        ];
+        assert_eq!(references(&document_types), [
+            Reference { from: 0, property: String::from("parentListingId"), to: 0 },
+            Reference { from: 0, property: String::from("offers[].bidId"), to: 1 },
+            Reference { from: 1, property: String::from("listingId"), to: 0 },
+        ]);
+    }
+
+    #[test]
+    fn writes_valid_mermaid_names() {
+        assert_eq!(mermaid_name("listing"), "listing");
+        assert_eq!(mermaid_name("offers[].bidId"), "offers___bidId");
+        assert_eq!(mermaid_name("2fa"), "_2fa");
+        assert_eq!(mermaid_name(""), "_");
+        let diagram = mermaid(&[document_type("3d-model", vec![identifier("listingId")])]);
+        assert!(diagram.contains("    _3d-model {\n        identifier listingId\n    }\n"));
+    }
+}
@@ -0,0 +1,561 @@
+//! Semantic diff between two versions of a contract, built on the document type model,
+//! and the update rules Platform applies when a registered contract is changed.
+
+use crate::{indexing::find_property, ContractSettings, DataType, DocumentType, Index, NumericConstraint, Property};
+
+/// What changed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ChangeKind {
+    /// A contract-level setting such as `readonly` or `keywords`
+    ContractSettingChanged(&'static str),
+    DocumentTypeAdded,
+    DocumentTypeRemoved,
+    /// `$comment` of the document type
+    CommentChanged,
+    AdditionalPropertiesChanged,
+    /// A document type setting such as `documentsMutable`
+    SettingChanged(&'static str),
+    PropertyAdded,
+    PropertyRemoved,
+    TypeChanged,
+    RequiredChanged,
+    /// A validation parameter such as `maxLength`
+    ConstraintChanged(&'static str),
+    /// `description` or `$comment` of a property, which don't affect validation
+    AnnotationChanged(&'static str),
+    IndexAdded,
+    IndexRemoved,
+    IndexChanged,
+}
+
+/// A single change between two contracts
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    pub document_type: String,
+    pub kind: ChangeKind,
+    /// Dotted property path, with `[]` for array items, or index name. Empty for changes to the document type itself
+    pub subject: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl Change {
+    fn new(document_type: &str, kind: ChangeKind, subject: &str, old: Option<String>, new: Option<String>) -> Self {
+        Self { document_type: document_type.to_string(), kind, subject: subject.to_string(), old, new }
+    }
+
+    /// Human-readable summary, e.g. `nft.name: maxLength changed from 63 to 32`
+    pub fn description(&self) -> String {
+        let target = if self.subject.is_empty() {
+            self.document_type.clone()
+        } else {
+            format!("{}.{}", self.document_type, self.subject)
+        };
+        let old = self.old.clone().unwrap_or_else(|| String::from("none"));
+        let new = self.new.clone().unwrap_or_else(|| String::from("none"));
+        match self.kind {
+            ChangeKind::ContractSettingChanged(name) => format!("Contract: {} changed from {} to {}", name, old, new),
+            ChangeKind::DocumentTypeAdded => format!("Document type \"{}\" added", self.document_type),
+            ChangeKind::DocumentTypeRemoved => format!("Document type \"{}\" removed", self.document_type),
+            ChangeKind::CommentChanged => format!("{}: $comment changed", target),
+            ChangeKind::AdditionalPropertiesChanged => format!("{}: additionalProperties changed from {} to {}", target, old, new),
+            ChangeKind::SettingChanged(name) => format!("{}: {} changed from {} to {}", target, name, old, new),
+            ChangeKind::PropertyAdded => format!("{}: property added ({})", target, new),
+            ChangeKind::PropertyRemoved => format!("{}: property removed ({})", target, old),
+            ChangeKind::TypeChanged => format!("{}: type changed from {} to {}", target, old, new),
+            ChangeKind::RequiredChanged => format!("{}: {}", target, if self.new.as_deref() == Some("true") { "became required" } else { "no longer required" }),
+            ChangeKind::ConstraintChanged(name) => format!("{}: {} changed from {} to {}", target, name, old, new),
+            ChangeKind::AnnotationChanged(name) => format!("{}: {} changed", target, name),
+            ChangeKind::IndexAdded => format!("{}: index \"{}\" added ({})", self.document_type, self.subject, new),
+            ChangeKind::IndexRemoved => format!("{}: index \"{}\" removed ({})", self.document_type, self.subject, old),
+            ChangeKind::IndexChanged => format!("{}: index \"{}\" changed from {} to {}", self.document_type, self.subject, old, new),
+        }
+    }
+
+    /// Whether something was added, removed or changed, for styling
+    pub fn status(&self) -> &'static str {
+        match self.kind {
+            ChangeKind::DocumentTypeAdded | ChangeKind::PropertyAdded | ChangeKind::IndexAdded => "added",
+            ChangeKind::DocumentTypeRemoved | ChangeKind::PropertyRemoved | ChangeKind::IndexRemoved => "removed",
+            _ => "changed",
+        }
+    }
+
+    /// What the change applies to, e.g. `name`, `index byName` or `additionalProperties`
+    pub fn label(&self) -> String {
+        match self.kind {
+            ChangeKind::DocumentTypeAdded | ChangeKind::DocumentTypeRemoved => String::from("(document type)"),
+            ChangeKind::IndexAdded | ChangeKind::IndexRemoved | ChangeKind::IndexChanged => format!("index {}", self.subject),
+            ChangeKind::CommentChanged => String::from("$comment"),
+            ChangeKind::AdditionalPropertiesChanged if self.subject.is_empty() => String::from("additionalProperties"),
+            ChangeKind::SettingChanged(name) | ChangeKind::ContractSettingChanged(name) => name.to_string(),
+            ChangeKind::ConstraintChanged(name) | ChangeKind::AnnotationChanged(name) => format!("{} {}", self.subject, name),
+            ChangeKind::AdditionalPropertiesChanged => format!("{} additionalProperties", self.subject),
+            ChangeKind::RequiredChanged => format!("{} required", self.subject),
+            ChangeKind::TypeChanged => format!("{} type", self.subject),
+            _ => self.subject.clone(),
+        }
+    }
+
+    /// The old and new side of the change for a side-by-side view, empty where absent
+    pub fn sides(&self) -> (String, String) {
+        match self.kind {
+            ChangeKind::DocumentTypeAdded => (String::new(), String::from("present")),
+            ChangeKind::DocumentTypeRemoved => (String::from("present"), String::new()),
+            ChangeKind::RequiredChanged => {
+                let side = |value: &Option<String>| if value.as_deref() == Some("true") { String::from("required") } else { String::from("optional") };
+                (side(&self.old), side(&self.new))
+            },
+            _ => (self.old.clone().unwrap_or_default(), self.new.clone().unwrap_or_default()),
+        }
+    }
+
+    /// Why Platform would likely reject this change in a contract update, as a readable hint next to the diff.
+    /// Platform has the final say. `previous` is the registered version and `current` the updated one.
+    pub fn update_rejection(&self, previous: &[DocumentType], current: &[DocumentType]) -> Option<&'static str> {
+        match self.kind {
+            ChangeKind::DocumentTypeAdded | ChangeKind::CommentChanged | ChangeKind::AnnotationChanged(_) => None,
+            ChangeKind::ContractSettingChanged("description" | "keywords") => None,
+            ChangeKind::ContractSettingChanged(_) => Some("The contract config can't be changed"),
+            ChangeKind::DocumentTypeRemoved => Some("Document types can't be removed"),
+            ChangeKind::AdditionalPropertiesChanged => Some("additionalProperties can't be changed"),
+            ChangeKind::SettingChanged(_) => Some("Document type settings can't be changed"),
+            ChangeKind::PropertyAdded => {
+                let required = find_document_type(current, &self.document_type)
+                    .and_then(|document_type| find_changed_property(&document_type.properties, &self.subject).map(|property| property.required))
+                    .unwrap_or_default();
+                if required {
+                    Some("New properties can't be required, since existing documents don't have them")
+                } else {
+                    None
+                }
+            },
+            ChangeKind::PropertyRemoved => Some("Properties can't be removed"),
+            ChangeKind::TypeChanged => Some("Property types can't be changed"),
+            ChangeKind::RequiredChanged => Some("Required properties can't be changed"),
+            ChangeKind::ConstraintChanged(_) => Some("Validation parameters of existing properties can't be changed"),
+            ChangeKind::IndexRemoved => Some("Indices can't be removed"),
+            ChangeKind::IndexChanged => Some("Existing indices can't be changed"),
+            ChangeKind::IndexAdded => {
+                let index = find_document_type(current, &self.document_type)?.indices.iter().find(|index| index.name == self.subject)?;
+                if index.unique {
+                    return Some("Unique indices can't be added to existing document types");
+                }
+                let existing = find_document_type(previous, &self.document_type)?;
+                let indexes_existing = index.properties.iter()
+                    .any(|property| property.name.starts_with('$') || find_property(&existing.properties, &property.name).is_some());
+                if indexes_existing {
+                    Some("New indices can only cover properties added in this update")
+                } else {
+                    None
+                }
+            },
+        }
+    }
+}
+
+/// Name of a data type as written in the contract
+fn type_name(property: &Property) -> String {
+    match property.data_type {
+        DataType::String => String::from("string"),
+        DataType::Integer => String::from("integer"),
+        DataType::Number => String::from("number"),
+        DataType::Boolean => String::from("boolean"),
+        DataType::Array if property.byte_array == Some(true) => String::from("byte array"),
+        DataType::Array => match property.items.as_deref() {
+            Some(items) => format!("array of {}", type_name(items)),
+            None => String::from("array"),
+        },
+        DataType::Object => String::from("object"),
+    }
+}
+
+/// Validation parameters of a property, by their name in the contract
+fn constraints(property: &Property) -> Vec<(&'static str, Option<String>)> {
+    let mut constraints = vec![
+        ("minLength", property.min_length.map(|v| v.to_string())),
+        ("maxLength", property.max_length.map(|v| v.to_string())),
+        ("pattern", property.pattern.clone().filter(|v| !v.is_empty())),
+        ("format", property.format.clone().filter(|v| !v.is_empty())),
+    ];
+    constraints.extend(NumericConstraint::ALL.iter().map(|c| (c.key(), c.get(property).map(|v| v.to_string()))));
+    constraints.extend([
+        ("minItems", property.min_items.map(|v| v.to_string())),
+        ("maxItems", property.max_items.map(|v| v.to_string())),
+        ("uniqueItems", property.unique_items.map(|v| v.to_string())),
+        ("contentMediaType", property.content_media_type.clone().filter(|v| !v.is_empty())),
+        ("minProperties", property.min_properties.map(|v| v.to_string())),
+        ("maxProperties", property.max_properties.map(|v| v.to_string())),
+    ]);
+    constraints
+}
+
+fn find_document_type<'a>(document_types: &'a [DocumentType], name: &str) -> Option<&'a DocumentType> {
+    document_types.iter().find(|document_type| document_type.name == name)
+}
+
+/// Finds a property by the path of a change, where `[]` steps into an array's items, e.g. `tags[].label`
+fn find_changed_property<'a>(properties: &'a [Property], path: &str) -> Option<&'a Property> {
+    let (name, rest) = match path.split_once('.') {
+        Some((name, rest)) => (name, Some(rest)),
+        None => (path, None),
+    };
+    let property = match name.strip_suffix("[]") {
+        Some(name) => properties.iter().find(|property| property.name == name)?.items.as_deref()?,
+        None => properties.iter().find(|property| property.name == name)?,
+    };
+    match rest {
+        Some(rest) => find_changed_property(property.properties.as_deref()?, rest),
+        None => Some(property),
+    }
+}
+
+/// Summary of a property for added and removed changes, e.g. `string, required`
+fn property_summary(property: &Property) -> String {
+    if property.required {
+        format!("{}, required", type_name(property))
+    } else {
+        type_name(property)
+    }
+}
+
+/// Summary of an index, e.g. `unique: ownerId asc, name asc`
+fn index_summary(index: &Index) -> String {
+    let properties = index.properties.iter()
+        .map(|property| format!("{} {}", property.name, property.order))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let null_searchable = if index.null_searchable == Some(false) { ", not null-searchable" } else { "" };
+    format!("{}: {}{}", if index.unique { "unique" } else { "non-unique" }, properties, null_searchable)
+}
+
+fn diff_properties(document_type: &str, old: &[Property], new: &[Property], prefix: &str, changes: &mut Vec<Change>) {
+    for old_property in old {
+        let path = format!("{}{}", prefix, old_property.name);
+        if !new.iter().any(|property| property.name == old_property.name) {
+            changes.push(Change::new(document_type, ChangeKind::PropertyRemoved, &path, Some(property_summary(old_property)), None));
+        }
+    }
+    for new_property in new {
+        let path = format!("{}{}", prefix, new_property.name);
+        let old_property = match old.iter().find(|property| property.name == new_property.name) {
+            Some(property) => property,
+            None => {
+                changes.push(Change::new(document_type, ChangeKind::PropertyAdded, &path, None, Some(property_summary(new_property))));
+                continue;
+            }
+        };
+        let (old_type, new_type) = (type_name(old_property), type_name(new_property));
+        if old_type != new_type {
+            changes.push(Change::new(document_type, ChangeKind::TypeChanged, &path, Some(old_type), Some(new_type)));
+            continue;
+        }
+        if old_property.required != new_property.required {
+            changes.push(Change::new(document_type, ChangeKind::RequiredChanged, &path,
+                Some(old_property.required.to_string()), Some(new_property.required.to_string())));
+        }
+        for ((name, old_value), (_, new_value)) in constraints(old_property).into_iter().zip(constraints(new_property)) {
+            if old_value != new_value {
+                changes.push(Change::new(document_type, ChangeKind::ConstraintChanged(name), &path, old_value, new_value));
+            }
+        }
+        if new_property.data_type == DataType::Object && old_property.additional_properties != new_property.additional_properties {
+            changes.push(Change::new(document_type, ChangeKind::AdditionalPropertiesChanged, &path,
+                old_property.additional_properties.map(|v| v.to_string()), new_property.additional_properties.map(|v| v.to_string())));
+        }
+        if old_property.description.as_deref().unwrap_or_default() != new_property.description.as_deref().unwrap_or_default() {
+            changes.push(Change::new(document_type, ChangeKind::AnnotationChanged("description"), &path,
+                old_property.description.clone(), new_property.description.clone()));
+        }
+        if old_property.comment.as_deref().unwrap_or_default() != new_property.comment.as_deref().unwrap_or_default() {
+            changes.push(Change::new(document_type, ChangeKind::AnnotationChanged("$comment"), &path,
+                old_property.comment.clone(), new_property.comment.clone()));
+        }
+        diff_properties(
+            document_type,
+            old_property.properties.as_deref().map(|p| p.as_slice()).unwrap_or_default(),
+            new_property.properties.as_deref().map(|p| p.as_slice()).unwrap_or_default(),
+            &format!("{}.", path),
+            changes,
+        );
+        // Equal type names mean both arrays have items of the same type, or neither has
+        if let (Some(old_items), Some(new_items)) = (old_property.items.as_deref(), new_property.items.as_deref()) {
+            let items_path = format!("{}[]", path);
+            for ((name, old_value), (_, new_value)) in constraints(old_items).into_iter().zip(constraints(new_items)) {
+                if old_value != new_value {
+                    changes.push(Change::new(document_type, ChangeKind::ConstraintChanged(name), &items_path, old_value, new_value));
+                }
+            }
+            diff_properties(
+                document_type,
+                old_items.properties.as_deref().map(|p| p.as_slice()).unwrap_or_default(),
+                new_items.properties.as_deref().map(|p| p.as_slice()).unwrap_or_default(),
+                &format!("{}.", items_path),
+                changes,
+            );
+        }
+    }
+}
+
+fn diff_document_type(old: &DocumentType, new: &DocumentType, changes: &mut Vec<Change>) {
+    let name = new.name.as_str();
+    if old.comment.trim() != new.comment.trim() {
+        changes.push(Change::new(name, ChangeKind::CommentChanged, "", Some(old.comment.clone()), Some(new.comment.clone())));
+    }
+    if old.additionalProperties != new.additionalProperties {
+        changes.push(Change::new(name, ChangeKind::AdditionalPropertiesChanged, "",
+            Some(old.additionalProperties.to_string()), Some(new.additionalProperties.to_string())));
+    }
+    let settings = [
+        ("documentsMutable", old.documents_mutable, new.documents_mutable),
+        ("canBeDeleted", old.can_be_deleted, new.can_be_deleted),
+        ("documentsKeepHistory", old.documents_keep_history, new.documents_keep_history),
+    ];
+    for (setting, old_value, new_value) in settings {
+        if old_value != new_value {
+            changes.push(Change::new(name, ChangeKind::SettingChanged(setting), "",
+                old_value.map(|v| v.to_string()), new_value.map(|v| v.to_string())));
+        }
+    }
+    let system_fields = [
+        ("$createdAt", old.created_at_required, new.created_at_required),
+        ("$updatedAt", old.updated_at_required, new.updated_at_required),
+    ];
+    for (field, old_required, new_required) in system_fields {
+        if old_required != new_required {
+            changes.push(Change::new(name, ChangeKind::RequiredChanged, field, Some(old_required.to_string()), Some(new_required.to_string())));
+        }
+    }
+    diff_properties(name, &old.properties, &new.properties, "", changes);
+    for old_index in &old.indices {
+        match new.indices.iter().find(|index| index.name == old_index.name) {
+            None => changes.push(Change::new(name, ChangeKind::IndexRemoved, &old_index.name, Some(index_summary(old_index)), None)),
+            Some(new_index) => {
+                let (old_summary, new_summary) = (index_summary(old_index), index_summary(new_index));
+                if old_summary != new_summary {
+                    changes.push(Change::new(name, ChangeKind::IndexChanged, &old_index.name, Some(old_summary), Some(new_summary)));
+                }
+            },
+        }
+    }
+    for new_index in &new.indices {
+        if !old.indices.iter().any(|index| index.name == new_index.name) {
+            changes.push(Change::new(name, ChangeKind::IndexAdded, &new_index.name, None, Some(index_summary(new_index))));
+        }
+    }
+}
+
+/// Lists the changes to the contract's config, description and keywords
+pub fn diff_settings(old: &ContractSettings, new: &ContractSettings) -> Vec<Change> {
+    let flags = [
+        ("readonly", old.readonly, new.readonly),
+        ("keepsHistory", old.keeps_history, new.keeps_history),
+        ("canBeDeleted", old.can_be_deleted, new.can_be_deleted),
+        ("documentsMutableContractDefault", old.documents_mutable_contract_default, new.documents_mutable_contract_default),
+        ("documentsKeepHistoryContractDefault", old.documents_keep_history_contract_default, new.documents_keep_history_contract_default),
+    ];
+    let mut changes: Vec<Change> = flags.iter()
+        .filter(|(_, old_value, new_value)| old_value != new_value)
+        .map(|(name, old_value, new_value)| Change::new("", ChangeKind::ContractSettingChanged(name), "", Some(old_value.to_string()), Some(new_value.to_string())))
+        .collect();
+    let text = |value: &str| Some(value.trim().to_string()).filter(|value| !value.is_empty());
+    if old.description.trim() != new.description.trim() {
+        changes.push(Change::new("", ChangeKind::ContractSettingChanged("description"), "", text(&old.description), text(&new.description)));
+    }
+    if old.keyword_list() != new.keyword_list() {
+        changes.push(Change::new("", ChangeKind::ContractSettingChanged("keywords"), "",
+            text(&old.keyword_list().join(", ")), text(&new.keyword_list().join(", "))));
+    }
+    changes
+}
+
+/// Lists the changes from `old` to `new`, matching document types, properties and indices by name
+pub fn diff(old: &[DocumentType], new: &[DocumentType]) -> Vec<Change> {
+    let mut changes = Vec::new();
+    for old_type in old {
+        if !new.iter().any(|document_type| document_type.name == old_type.name) {
+            changes.push(Change::new(&old_type.name, ChangeKind::DocumentTypeRemoved, "", None, None));
+        }
+    }
+    for new_type in new {
+        match old.iter().find(|document_type| document_type.name == new_type.name) {
+            Some(old_type) => diff_document_type(old_type, new_type, &mut changes),
+            None => changes.push(Change::new(&new_type.name, ChangeKind::DocumentTypeAdded, "", None, None)),
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::IndexProperties;
+
+    /// A `note` document type with a `tags` array of `{ label }` objects
+    fn tags(max_length: u32) -> DocumentType {
+        let label = Property { name: String::from("label"), data_type: DataType::String, max_length: Some(max_length), ..Default::default() };
+        let items = Property { data_type: DataType::Object, properties: Some(Box::new(vec![label])), ..Default::default() };
+        let tags = Property { name: String::from("tags"), data_type: DataType::Array, items: Some(Box::new(items)), ..Default::default() };
+        DocumentType { name: String::from("note"), properties: vec![tags], ..Default::default() }
+    }
+
+    #[test]
+    fn diffs_fields_of_array_items() {
+        let old = tags(20);
+        let new = tags(30);
+        let changes = diff(&[old], &[new]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].subject, "tags[].label");
+        assert_eq!(changes[0].kind, ChangeKind::ConstraintChanged("maxLength"));
+    }
+
+    #[test]
+    fn diffs_constraints_of_array_items() {
+        let old = tags(20);
+        let mut new = tags(20);
+        new.properties[0].items.as_mut().unwrap().min_properties = Some(1);
+        let changes = diff(&[old], &[new]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].subject, "tags[]");
+        assert_eq!(changes[0].kind, ChangeKind::ConstraintChanged("minProperties"));
+    }
+
+    #[test]
+    fn finds_required_fields_added_to_array_items() {
+        let old = tags(20);
+        let mut new = tags(20);
+        let weight = Property { name: String::from("weight"), data_type: DataType::Integer, required: true, ..Default::default() };
+        new.properties[0].items.as_mut().unwrap().properties.as_mut().unwrap().push(weight);
+        let changes = diff(&[old.clone()], &[new.clone()]);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].subject, "tags[].weight");
+        assert!(changes[0].update_rejection(&[old], &[new]).is_some());
+    }
+
+    fn property(name: &str, data_type: DataType) -> Property {
+        Property { name: name.to_string(), data_type, ..Default::default() }
+    }
+
+    fn index(name: &str, properties: &[&str], unique: bool) -> Index {
+        Index {
+            name: name.to_string(),
+            properties: properties.iter().map(|name| IndexProperties { name: name.to_string(), order: String::from("asc") }).collect(),
+            unique,
+            null_searchable: None,
+        }
+    }
+
+    /// A `post` document type with `title` and `score`, indexed by title
+    fn post() -> DocumentType {
+        DocumentType {
+            name: String::from("post"),
+            properties: vec![property("title", DataType::String), property("score", DataType::Integer)],
+            indices: vec![index("byTitle", &["title"], false)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn diffs_document_types() {
+        let comment = DocumentType { name: String::from("comment"), ..Default::default() };
+        let changes = diff(&[post()], &[comment]);
+        assert_eq!(changes.len(), 2);
+        assert_eq!((changes[0].document_type.as_str(), changes[0].kind), ("post", ChangeKind::DocumentTypeRemoved));
+        assert_eq!((changes[1].document_type.as_str(), changes[1].kind), ("comment", ChangeKind::DocumentTypeAdded));
+        assert!(diff(&[post()], &[post()]).is_empty());
+    }
+
+    #[test]
+    fn diffs_properties() {
+        let mut new = post();
+        new.properties.retain(|property| property.name != "title");
+        new.properties[0].required = true;
+        new.properties[0].maximum = Some(100.into());
+        new.properties.push(property("body", DataType::String));
+        let changes = diff(&[post()], &[new.clone()]);
+        let kinds: Vec<(&str, ChangeKind)> = changes.iter().map(|change| (change.subject.as_str(), change.kind)).collect();
+        assert_eq!(kinds, [
+            ("title", ChangeKind::PropertyRemoved),
+            ("score", ChangeKind::RequiredChanged),
+            ("score", ChangeKind::ConstraintChanged("maximum")),
+            ("body", ChangeKind::PropertyAdded),
+        ]);
+        assert_eq!(changes[2].description(), "post.score: maximum changed from none to 100");
+
+        new.properties[0].data_type = DataType::String;
+        let changes = diff(&[post()], &[new]);
+        let retyped = changes.iter().find(|change| change.subject == "score").unwrap();
+        assert_eq!(retyped.kind, ChangeKind::TypeChanged);
+        assert_eq!((retyped.old.as_deref(), retyped.new.as_deref()), (Some("integer"), Some("string")));
+    }
+
+    #[test]
+    fn diffs_indices() {
+        let mut new = post();
+        new.indices[0].unique = true;
+        new.indices.push(index("byScore", &["score"], false));
+        let mut old = post();
+        old.indices.push(index("byTitleAndScore", &["title", "score"], false));
+        let changes = diff(&[old], &[new]);
+        let kinds: Vec<(&str, ChangeKind)> = changes.iter().map(|change| (change.subject.as_str(), change.kind)).collect();
+        assert_eq!(kinds, [
+            ("byTitle", ChangeKind::IndexChanged),
+            ("byTitleAndScore", ChangeKind::IndexRemoved),
+            ("byScore", ChangeKind::IndexAdded),
+        ]);
+        assert_eq!(changes[0].description(), "post: index \"byTitle\" changed from non-unique: title asc to unique: title asc");
+    }
+
+    #[test]
+    fn diffs_settings() {
+        let mut new = post();
+        new.documents_mutable = Some(false);
+        new.additionalProperties = !new.additionalProperties;
+        let changes = diff(&[post()], &[new]);
+        let kinds: Vec<ChangeKind> = changes.iter().map(|change| change.kind).collect();
+        assert_eq!(kinds, [ChangeKind::AdditionalPropertiesChanged, ChangeKind::SettingChanged("documentsMutable")]);
+
+        let old = ContractSettings::default();
+        let new = ContractSettings { readonly: true, keywords: String::from("notes, social"), ..Default::default() };
+        let changes = diff_settings(&old, &new);
+        let kinds: Vec<ChangeKind> = changes.iter().map(|change| change.kind).collect();
+        assert_eq!(kinds, [ChangeKind::ContractSettingChanged("readonly"), ChangeKind::ContractSettingChanged("keywords")]);
+        assert_eq!(changes[1].new.as_deref(), Some("notes, social"));
+        assert!(diff_settings(&old, &ContractSettings { keywords: String::from(" , "), ..Default::default() }).is_empty());
+    }
+
+    #[test]
+    fn flags_updates_platform_rejects() {
+        let old = post();
+        let mut new = post();
+        new.properties[0].max_length = Some(10);
+        new.properties.push(property("body", DataType::String));
+        let mut required = property("author", DataType::String);
+        required.required = true;
+        new.properties.push(required);
+        new.indices.push(index("byBody", &["body"], false));
+        new.indices.push(index("byScore", &["score"], false));
+        new.indices.push(index("byOwner", &["$ownerId"], false));
+        new.indices.push(index("uniqueBody", &["body"], true));
+        let (previous, current) = ([old], [new]);
+        let rejection = |subject: &str| {
+            let changes = diff(&previous, &current);
+            let change = changes.iter().find(|change| change.subject == subject).unwrap();
+            change.update_rejection(&previous, &current)
+        };
+        assert_eq!(rejection("title"), Some("Validation parameters of existing properties can't be changed"));
+        assert_eq!(rejection("body"), None);
+        assert_eq!(rejection("author"), Some("New properties can't be required, since existing documents don't have them"));
+        assert_eq!(rejection("byBody"), None);
+        assert_eq!(rejection("byScore"), Some("New indices can only cover properties added in this update"));
+        assert_eq!(rejection("byOwner"), Some("New indices can only cover properties added in this update"));
+        assert_eq!(rejection("uniqueBody"), Some("Unique indices can't be added to existing document types"));
+
+        let removed = diff(&previous, &[]);
+        assert_eq!(removed[0].update_rejection(&previous, &[]), Some("Document types can't be removed"));
+        let settings = ContractSettings { keeps_history: true, description: String::from("Notes"), ..Default::default() };
+        let changes = diff_settings(&ContractSettings::default(), &settings);
+        assert_eq!(changes[0].update_rejection(&previous, &previous), Some("The contract config can't be changed"));
+        assert_eq!(changes[1].update_rejection(&previous, &previous), None);
+    }
+}
@@ -0,0 +1,337 @@
+//! Generates human-readable documentation of the contract for integrators, as Markdown or HTML.
+
+use crate::{DataType, DocumentType, NumericConstraint, Property};
+
+/// A row of a document type's property table
+struct PropertyRow {
+    name: String,
+    data_type: String,
+    required: bool,
+    constraints: Vec<String>,
+    description: String,
+}
+
+/// Name of the data type as written in the contract
+fn type_name(property: &Property) -> String {
+    match property.data_type {
+        DataType::String => String::from("string"),
+        DataType::Integer => String::from("integer"),
+        DataType::Number => String::from("number"),
+        DataType::Boolean => String::from("boolean"),
+        DataType::Array if property.byte_array == Some(true) => String::from("array (bytes)"),
+        DataType::Array => match property.items.as_deref() {
+            Some(items) => format!("array of {}", type_name(items)),
+            None => String::from("array"),
+        },
+        DataType::Object => String::from("object"),
+    }
+}
+
+/// The validation parameters set on a property, e.g. `maxLength: 63`
+fn constraints(property: &Property) -> Vec<String> {
+    let mut constraints = Vec::new();
+    let mut push = |name: &str, value: Option<String>| {
+        if let Some(value) = value {
+            constraints.push(format!("{}: {}", name, value));
+        }
+    };
+    push("minLength", property.min_length.map(|v| v.to_string()));
+    push("maxLength", property.max_length.map(|v| v.to_string()));
+    push("pattern", property.pattern.clone().filter(|v| !v.is_empty()));
+    push("format", property.format.clone().filter(|v| !v.is_empty()));
+    for constraint in NumericConstraint::ALL {
+        push(constraint.key(), constraint.get(property).map(|v| v.to_string()));
+    }
+    push("minItems", property.min_items.map(|v| v.to_string()));
+    push("maxItems", property.max_items.map(|v| v.to_string()));
+    push("uniqueItems", property.unique_items.map(|v| v.to_string()));
+    push("contentMediaType", property.content_media_type.clone().filter(|v| !v.is_empty()));
+    push("minProperties", property.min_properties.map(|v| v.to_string()));
+    push("maxProperties", property.max_properties.map(|v| v.to_string()));
+    if property.data_type == DataType::Object {
+        push("additionalProperties", property.additional_properties.map(|v| v.to_string()));
+    }
+    constraints
+}
+
+/// Flattens properties into table rows, naming nested properties by their dotted path
+/// and array items by the array's path plus `[]`
+fn property_rows(properties: &[Property], prefix: &str, rows: &mut Vec<PropertyRow>) {
+    for property in properties {
+        let name = format!("{}{}", prefix, property.name);
+        let mut description = property.description.clone().unwrap_or_default();
+        if let Some(comment) = property.comment.as_deref().filter(|c| !c.trim().is_empty()) {
+            if !description.is_empty() {
+                description.push(' ');
+            }
+            description.push_str(&format!("({})", comment));
+        }
+        rows.push(PropertyRow {
+            name: name.clone(),
+            data_type: type_name(property),
+            required: property.required,
+            constraints: constraints(property),
+            description,
+        });
+        if let Some(nested) = property.properties.as_deref() {
+            property_rows(nested, &format!("{}.", name), rows);
+        }
+        if let Some(items) = property.items.as_deref() {
+            let items_name = format!("{}[]", name);
+            rows.push(PropertyRow {
+                name: items_name.clone(),
+                data_type: type_name(items),
+                required: false,
+                constraints: constraints(items),
+                description: String::from("Each item of the array"),
+            });
+            if let Some(fields) = items.properties.as_deref() {
+                property_rows(fields, &format!("{}.", items_name), rows);
+            }
+        }
+    }
+}
+
+/// Property table rows of a document type, including its required system fields
+fn document_rows(document_type: &DocumentType) -> Vec<PropertyRow> {
+    let mut rows = Vec::new();
+    let system_fields = [
+        ("$createdAt", document_type.created_at_required),
+        ("$updatedAt", document_type.updated_at_required),
+    ];
+    for (name, required) in system_fields {
+        if required {
+            rows.push(PropertyRow {
+                name: name.to_string(),
+                data_type: String::from("integer"),
+                required: true,
+                constraints: vec![],
+                description: String::from("Platform system field, in milliseconds since the Unix epoch"),
+            });
+        }
+    }
+    property_rows(&document_type.properties, "", &mut rows);
+    rows
+}
+
+/// The index's properties in order, e.g. `ownerId (asc), name (desc)`
+fn index_properties(index: &crate::Index) -> String {
+    index.properties.iter()
+        .map(|property| format!("{} ({})", property.name, property.order))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The document type settings that are set, e.g. `Documents can't be deleted.`
+fn settings(document_type: &DocumentType) -> Vec<&'static str> {
+    let mut settings = Vec::new();
+    match document_type.documents_mutable {
+        Some(true) => settings.push("Documents can be updated."),
+        Some(false) => settings.push("Documents can't be updated."),
+        None => {},
+    }
+    match document_type.can_be_deleted {
+        Some(true) => settings.push("Documents can be deleted."),
+        Some(false) => settings.push("Documents can't be deleted."),
+        None => {},
+    }
+    if document_type.documents_keep_history == Some(true) {
+        settings.push("Platform keeps the history of each document.");
+    }
+    settings
+}
+
+/// Escapes text for a Markdown table cell
+fn md_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', "<br>")
+}
+
+/// Formats text as inline code in a Markdown table cell, fenced with more backticks than it contains in a row
+fn md_code(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or_default();
+    let fence = "`".repeat(longest + 1);
+    let padding = if text.starts_with('`') || text.ends_with('`') { " " } else { "" };
+    md_cell(&format!("{}{}{}{}{}", fence, padding, text, padding, fence))
+}
+
+/// Generates Markdown documentation with one section per document type
+pub fn markdown(title: &str, document_types: &[DocumentType]) -> String {
+    let mut out = format!("# {}\n\nData contract documentation, generated by Data Contract Creator.\n", title);
+    out.push_str("\n## Document types\n\n");
+    for document_type in document_types {
+        out.push_str(&format!("- [{}](#{})\n", document_type.name, document_type.name.to_lowercase()));
+    }
+    for document_type in document_types {
+        out.push_str(&format!("\n## {}\n\n", document_type.name));
+        if !document_type.comment.trim().is_empty() {
+            out.push_str(&format!("{}\n\n", document_type.comment.trim()));
+        }
+        if document_type.additionalProperties {
+            out.push_str("Documents may contain properties not listed below.\n\n");
+        }
+        let settings = settings(document_type);
+        if !settings.is_empty() {
+            out.push_str(&format!("{}\n\n", settings.join(" ")));
+        }
+        out.push_str("### Properties\n\n");
+        out.push_str("| Name | Type | Required | Constraints | Description |\n");
+        out.push_str("| --- | --- | --- | --- | --- |\n");
+        for row in document_rows(document_type) {
+            out.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                md_code(&row.name),
+                md_cell(&row.data_type),
+                if row.required { "yes" } else { "no" },
+                md_cell(&row.constraints.join(", ")),
+                md_cell(&row.description),
+            ));
+        }
+        out.push_str("\n### Indices\n\n");
+        if document_type.indices.is_empty() {
+            out.push_str("No indices.\n");
+            continue;
+        }
+        out.push_str("| Name | Unique | Properties |\n");
+        out.push_str("| --- | --- | --- |\n");
+        for index in &document_type.indices {
+            out.push_str(&format!(
+                "| {} | {} | {} |\n",
+                md_code(&index.name),
+                if index.unique { "yes" } else { "no" },
+                md_cell(&index_properties(index)),
+            ));
+        }
+    }
+    out
+}
+
+/// Escapes text for HTML
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Generates a standalone HTML page with one section per document type
+pub fn html(title: &str, document_types: &[DocumentType]) -> String {
+    let title = escape_html(title);
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n\
+         body {{ font-family: sans-serif; max-width: 960px; margin: 2em auto; }}\n\
+         table {{ border-collapse: collapse; width: 100%; }}\n\
+         th, td {{ border: 1px solid #ccc; padding: 4px 8px; text-align: left; vertical-align: top; }}\n\
+         </style>\n</head>\n<body>\n<h1>{title}</h1>\n<p>Data contract documentation, generated by Data Contract Creator.</p>\n"
+    );
+    out.push_str("<h2>Document types</h2>\n<ul>\n");
+    for document_type in document_types {
+        let name = escape_html(&document_type.name);
+        out.push_str(&format!("<li><a href=\"#{}\">{}</a></li>\n", name, name));
+    }
+    out.push_str("</ul>\n");
+    for document_type in document_types {
+        let name = escape_html(&document_type.name);
+        out.push_str(&format!("<section id=\"{}\">\n<h2>{}</h2>\n", name, name));
+        if !document_type.comment.trim().is_empty() {
+            out.push_str(&format!("<p>{}</p>\n", escape_html(document_type.comment.trim())));
+        }
+        if document_type.additionalProperties {
+            out.push_str("<p>Documents may contain properties not listed below.</p>\n");
+        }
+        let settings = settings(document_type);
+        if !settings.is_empty() {
+            out.push_str(&format!("<p>{}</p>\n", settings.join(" ")));
+        }
+        out.push_str("<h3>Properties</h3>\n<table>\n");
+        out.push_str("<tr><th>Name</th><th>Type</th><th>Required</th><th>Constraints</th><th>Description</th></tr>\n");
+        for row in document_rows(document_type) {
+            out.push_str(&format!(
+                "<tr><td><code>{}</code></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>\n",
+                escape_html(&row.name),
+                row.data_type,
+                if row.required { "yes" } else { "no" },
+                escape_html(&row.constraints.join(", ")),
+                escape_html(&row.description).replace('\n', "<br>"),
+            ));
+        }
+        out.push_str("</table>\n<h3>Indices</h3>\n");
+        if document_type.indices.is_empty() {
+            out.push_str("<p>No indices.</p>\n");
+        } else {
+            out.push_str("<table>\n<tr><th>Name</th><th>Unique</th><th>Properties</th></tr>\n");
+            for index in &document_type.indices {
+                out.push_str(&format!(
+                    "<tr><td><code>{}</code></td><td>{}</td><td>{}</td></tr>\n",
+                    escape_html(&index.name),
+                    if index.unique { "yes" } else { "no" },
+                    escape_html(&index_properties(index)),
+                ));
+            }
+            out.push_str("</table>\n");
+        }
+        out.push_str("</section>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Index, IndexProperties};
+
+    fn note() -> DocumentType {
+        let property = |name: &str, description: &str| Property {
+            name: name.to_string(),
+            data_type: DataType::String,
+            max_length: Some(63),
+            description: Some(description.to_string()),
+            ..Default::default()
+        };
+        DocumentType {
+            name: String::from("note"),
+            comment: String::from("A short note"),
+            properties: vec![property("message", "The text\nof the note"), property("a|b", "Uses `code` | pipes"), property("x`y", "")],
+            indices: vec![Index {
+                name: String::from("by|message"),
+                properties: vec![IndexProperties { name: String::from("message"), order: String::from("asc") }],
+                unique: true,
+                null_searchable: None,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn escapes_markdown_table_cells() {
+        assert_eq!(md_code("message"), "`message`");
+        assert_eq!(md_code("a|b"), "`a\\|b`");
+        assert_eq!(md_code("x`y"), "``x`y``");
+        assert_eq!(md_code("`y"), "`` `y ``");
+    }
+
+    #[test]
+    fn generates_markdown() {
+        let markdown = markdown("Notes", &[note()]);
+        assert!(markdown.starts_with("# Notes\n"));
+        assert!(markdown.contains("- [note](#note)\n"));
+        assert!(markdown.contains("\n## note\n\nA short note\n\n### Properties\n"));
+        assert!(markdown.contains("| `message` | string | no | maxLength: 63 | The text<br>of the note |\n"));
+        assert!(markdown.contains("| `a\\|b` | string | no | maxLength: 63 | Uses `code` \\| pipes |\n"));
+        assert!(markdown.contains("| ``x`y`` | string | no | maxLength: 63 |  |\n"));
+        assert!(markdown.contains("| `by\\|message` | yes | message (asc) |\n"));
+        // Every table row has as many cells as its header
+        for line in markdown.lines().filter(|line| line.starts_with('|')) {
+            let cells = line.replace("\\|", "").matches('|').count();
+            assert!(cells == 6 || cells == 4, "{}", line);
+        }
+    }
+
+    #[test]
+    fn generates_html() {
+        let html = html("Notes <draft>", &[note()]);
+        assert!(html.contains("<title>Notes &lt;draft&gt;</title>"));
+        assert!(html.contains("<tr><td><code>a|b</code></td><td>string</td><td>no</td><td>maxLength: 63</td><td>Uses `code` | pipes</td></tr>"));
+        assert!(html.contains("<td>The text<br>of the note</td>"));
+    }
+}
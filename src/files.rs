@@ -0,0 +1,78 @@
+//! Browser file helpers: downloading generated files and reading files chosen by the user.
+
+use wasm_bindgen::{prelude::{wasm_bindgen, Closure}, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Blob, BlobPropertyBag, File, HtmlAnchorElement, Url};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = ["navigator", "clipboard"], js_name = writeText, catch)]
+    fn write_clipboard_text(text: &str) -> Result<js_sys::Promise, JsValue>;
+}
+
+fn js_error(err: JsValue) -> anyhow::Error {
+    anyhow::anyhow!(err.as_string().unwrap_or_else(|| format!("{:?}", err)))
+}
+
+/// Offers `contents` to the user as a file download
+pub fn download(filename: &str, contents: &[u8], mime_type: &str) -> Result<(), anyhow::Error> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(contents));
+    let mut options = BlobPropertyBag::new();
+    options.type_(mime_type);
+    let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &options).map_err(js_error)?;
+    let url = Url::create_object_url_with_blob(&blob).map_err(js_error)?;
+
+    let window = web_sys::window().ok_or_else(|| anyhow::anyhow!("Failed to obtain window object"))?;
+    let document = window.document().ok_or_else(|| anyhow::anyhow!("Failed to obtain document object"))?;
+    let body = document.body().ok_or_else(|| anyhow::anyhow!("Failed to obtain document body"))?;
+    let anchor: HtmlAnchorElement = document.create_element("a").map_err(js_error)?
+        .dyn_into()
+        .map_err(|_| anyhow::anyhow!("Failed to create download link"))?;
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    // Some browsers ignore clicks on links that aren't in the document
+    body.append_child(&anchor).map_err(js_error)?;
+    anchor.click();
+    anchor.remove();
+
+    // Revoking the URL right away can cancel the download before the browser has read the blob
+    let revoke = Closure::once_into_js(move || {
+        let _ = Url::revoke_object_url(&url);
+    });
+    window.set_timeout_with_callback_and_timeout_and_arguments_0(revoke.unchecked_ref(), 60_000).map_err(js_error)?;
+    Ok(())
+}
+
+/// Copies text to the clipboard
+pub async fn copy_to_clipboard(text: &str) -> Result<(), anyhow::Error> {
+    let promise = write_clipboard_text(text).map_err(js_error)?;
+    JsFuture::from(promise).await.map_err(js_error)?;
+    Ok(())
+}
+
+/// Reads a file as UTF-8 text
+pub async fn read_text(file: File) -> Result<String, anyhow::Error> {
+    let text = JsFuture::from(file.text()).await.map_err(js_error)?;
+    text.as_string().ok_or_else(|| anyhow::anyhow!("Failed to read \"{}\" as text", file.name()))
+}
+
+/// Turns a name into a safe file name stem, e.g. "My Contract" -> "my-contract"
+pub fn file_stem(name: &str) -> String {
+    let stem = name
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if stem.is_empty() { String::from("contract") } else { stem }
+}
+
+/// Reads a file as raw bytes
+pub async fn read_bytes(file: File) -> Result<Vec<u8>, anyhow::Error> {
+    let buffer = JsFuture::from(file.array_buffer()).await.map_err(js_error)?;
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
@@ -0,0 +1,61 @@
+//! Which properties of a document type can be indexed, and the limits Platform puts on indexed properties.
+
+use crate::{DataType, DocumentType, Property, INDEXABLE_SYSTEM_FIELDS};
+
+/// Longest `maxLength` an indexed string may have
+pub const MAX_INDEXED_STRING_LENGTH: u32 = 63;
+
+/// Largest `maxItems` an indexed array may have
+pub const MAX_INDEXED_ARRAY_ITEMS: u32 = 255;
+
+/// Finds a property by its dotted path
+pub fn find_property<'a>(properties: &'a [Property], path: &str) -> Option<&'a Property> {
+    let (name, rest) = match path.split_once('.') {
+        Some((name, rest)) => (name, Some(rest)),
+        None => (path, None),
+    };
+    let property = properties.iter().find(|property| property.name == name)?;
+    match rest {
+        Some(rest) => find_property(property.properties.as_deref()?, rest),
+        None => Some(property),
+    }
+}
+
+fn collect_paths(properties: &[Property], prefix: &str, paths: &mut Vec<String>) {
+    for property in properties.iter().filter(|property| !property.name.is_empty()) {
+        let path = format!("{}{}", prefix, property.name);
+        match property.properties.as_deref() {
+            Some(nested) if property.data_type == DataType::Object => collect_paths(nested, &format!("{}.", path), paths),
+            _ => paths.push(path),
+        }
+    }
+}
+
+/// The system fields and the dotted paths of all non-object properties, in form order
+pub fn indexable_paths(document_type: &DocumentType) -> Vec<String> {
+    let mut paths: Vec<String> = INDEXABLE_SYSTEM_FIELDS.iter().map(|field| field.to_string()).collect();
+    collect_paths(&document_type.properties, "", &mut paths);
+    paths
+}
+
+/// Why the property at `path` can't be indexed as it is, if it can't
+pub fn index_property_problem(document_type: &DocumentType, path: &str) -> Option<String> {
+    if path.is_empty() || INDEXABLE_SYSTEM_FIELDS.contains(&path) {
+        return None;
+    }
+    let property = match find_property(&document_type.properties, path) {
+        Some(property) => property,
+        None => return Some(format!("\"{}\" is not a property of this document type.", path)),
+    };
+    match property.data_type {
+        DataType::String if property.max_length.map_or(true, |max| max > MAX_INDEXED_STRING_LENGTH) => {
+            Some(format!("Indexed strings need a maxLength of at most {}.", MAX_INDEXED_STRING_LENGTH))
+        },
+        DataType::Array if property.byte_array != Some(true) => Some(String::from("Only byte arrays can be indexed.")),
+        DataType::Array if property.max_items.map_or(true, |max| max > MAX_INDEXED_ARRAY_ITEMS) => {
+            Some(format!("Indexed arrays need a maxItems of at most {}.", MAX_INDEXED_ARRAY_ITEMS))
+        },
+        DataType::Object => Some(String::from("Objects can't be indexed. Index one of their properties instead.")),
+        _ => None,
+    }
+}
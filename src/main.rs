@@ -7,7 +7,7 @@
 use std::{collections::{HashMap, HashSet}, sync::Arc};
 use serde::{Serialize, Deserialize};
 use yew::{prelude::*, html, Component, Html, Event, InputEvent, TargetCast};
-use serde_json::{json, Map, Value};
+use serde_json::{json, Map, Number, Value};
 use dpp::{self, consensus::ConsensusError, prelude::Identifier, Convertible};
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::{JsFuture, spawn_local};
@@ -15,6 +15,18 @@ use web_sys::{Request, RequestInit, RequestMode, Response, HtmlSelectElement};
 #[allow(unused_imports)]
 use web_sys::console;
 
+mod codegen;
+mod dapi;
+mod diagram;
+mod diff;
+mod docs;
+mod files;
+mod indexing;
+mod query;
+mod registration;
+mod serialization;
+mod storage;
+
 
 // NOTE August 2023: This app originally used the OpenAI API text completions, but has now changed to chat completions.
 // The method of prepending a first prompt with information (a) and then prepending all subsequent prompts with information (b) can probably be replaced with something better.
@@ -41,7 +53,7 @@ The following requirements must be met in Hellar Platform data contracts:
  - Indexes may only have "asc" sort order. 
  - All "string" properties that are used in indexes must specify "maxLength", which must be no more than 63. 
  - All "array" properties that are used in indexes must specify "maxItems", and it must be less than or equal to 255. 
- - All "array" properties must specify `"byteArray": true`. 
+ - All "array" properties must either specify `"byteArray": true` or describe their elements with an "items" schema. Only byte arrays can be used in indexes. 
  - All "object" properties must define at least 1 property within themselves. 
 
 *App description*: 
@@ -51,7 +63,7 @@ When creating the data contract, please:
  - Include descriptions for every document type and property. Be creative, extensive, and utilize multiple document types if possible. 
  - Include indexes for any properties that it makes sense for a useful app to index. More is better. 
  - Do not explain anything or return anything else other than a properly formatted data contract JSON schema. 
- - Double check that all requirements and requests above are met. Again, all "array" properties must specify `"byteArray": true`. 
+ - Double check that all requirements and requests above are met. Again, all "array" properties must specify `"byteArray": true` or an "items" schema. 
 
 App description: 
 
@@ -64,7 +76,7 @@ I'm going to ask you to make some changes to a Hellar Platform data contract aft
 *Requirements*:
 The following requirements must be met in Hellar Platform data contracts: 
  - Indexes may only have "asc" sort order. 
- - All "array" properties must specify "byteArray": true. 
+ - All "array" properties must either specify "byteArray": true or describe their elements with an "items" schema. Only byte arrays can be used in indexes. 
  - All "string" properties that are used in indexes must specify "maxLength", which must be no more than 63. 
  - All "array" properties that are used in indexes must specify "maxItems", and it must be less than or equal to 255. 
  - All "object" properties must define at least 1 property within themselves. 
@@ -76,16 +88,141 @@ Do not explain anything or return anything else other than a properly formatted
 
 "#;
 
-/// Calls OpenAI
+// Chat completions model. Where the endpoint rejects structured outputs for it, the request is retried without them.
+const OPENAI_MODEL: &str = "gpt-3.5-turbo-16k";
+
+/// JSON schema describing a Platform data contract (a map of document type names to document schemas).
+/// Sent as the `response_format` so the model can only answer with a contract-shaped JSON object.
+fn contract_response_schema() -> Value {
+    json!({
+        "type": "object",
+        "description": "Hellar Platform data contract. Each key is a document type name and each value is the document type schema.",
+        "additionalProperties": { "$ref": "#/$defs/documentType" },
+        "$defs": {
+            "documentType": {
+                "type": "object",
+                "properties": {
+                    "type": { "type": "string", "enum": ["object"] },
+                    "properties": {
+                        "type": "object",
+                        "additionalProperties": { "$ref": "#/$defs/property" }
+                    },
+                    "indices": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "properties": {
+                                "name": { "type": "string" },
+                                "properties": {
+                                    "type": "array",
+                                    "items": {
+                                        "type": "object",
+                                        "additionalProperties": { "type": "string", "enum": ["asc"] }
+                                    }
+                                },
+                                "unique": { "type": "boolean" },
+                                "nullSearchable": { "type": "boolean" }
+                            },
+                            "required": ["name", "properties"]
+                        }
+                    },
+                    "required": { "type": "array", "items": { "type": "string" } },
+                    "additionalProperties": { "type": "boolean", "enum": [false] },
+                    "documentsMutable": { "type": "boolean" },
+                    "canBeDeleted": { "type": "boolean" },
+                    "documentsKeepHistory": { "type": "boolean" },
+                    "$comment": { "type": "string" }
+                },
+                "required": ["type", "properties", "additionalProperties"]
+            },
+            "property": {
+                "type": "object",
+                "properties": {
+                    "type": { "type": "string", "enum": ["string", "integer", "array", "object", "number", "boolean"] },
+                    "description": { "type": "string" },
+                    "minLength": { "type": "integer" },
+                    "maxLength": { "type": "integer" },
+                    "pattern": { "type": "string" },
+                    "format": { "type": "string" },
+                    "minimum": { "type": "number" },
+                    "maximum": { "type": "number" },
+                    "exclusiveMinimum": { "type": "number" },
+                    "exclusiveMaximum": { "type": "number" },
+                    "multipleOf": { "type": "number", "exclusiveMinimum": 0 },
+                    "byteArray": { "type": "boolean" },
+                    "minItems": { "type": "integer" },
+                    "maxItems": { "type": "integer" },
+                    "contentMediaType": { "type": "string" },
+                    "items": { "$ref": "#/$defs/property" },
+                    "uniqueItems": { "type": "boolean" },
+                    "properties": {
+                        "type": "object",
+                        "additionalProperties": { "$ref": "#/$defs/property" }
+                    },
+                    "required": { "type": "array", "items": { "type": "string" } },
+                    "additionalProperties": { "type": "boolean", "enum": [false] },
+                    "$comment": { "type": "string" }
+                },
+                "required": ["type"]
+            }
+        }
+    })
+}
+
+/// Failure of a single chat completion request
+enum CompletionError {
+    /// The endpoint rejected the structured output `response_format` parameter
+    UnsupportedResponseFormat,
+    Other(anyhow::Error),
+}
+
+impl From<anyhow::Error> for CompletionError {
+    fn from(err: anyhow::Error) -> Self {
+        CompletionError::Other(err)
+    }
+}
+
+/// Calls OpenAI, asking for schema-constrained output first and falling back to free text
+/// if the endpoint doesn't support it. Returns the extracted contract JSON.
 pub async fn call_openai(prompt: &str) -> Result<String, anyhow::Error> {
-    let params = serde_json::json!({
-        "model": "gpt-3.5-turbo-16k",
+    let structured_params = serde_json::json!({
+        "model": OPENAI_MODEL,
         "messages": [{"role": "user", "content": prompt}],
         "max_tokens": 8000,
-        "temperature": 0.2
+        "temperature": 0.2,
+        "response_format": {
+            "type": "json_schema",
+            "json_schema": {
+                "name": "data_contract",
+                "schema": contract_response_schema(),
+                "strict": false
+            }
+        }
     });
-    let params = params.to_string();
 
+    let schema_text = match request_completion(&structured_params.to_string()).await {
+        Ok(text) => text,
+        Err(CompletionError::UnsupportedResponseFormat) => {
+            let params = serde_json::json!({
+                "model": OPENAI_MODEL,
+                "messages": [{"role": "user", "content": prompt}],
+                "max_tokens": 8000,
+                "temperature": 0.2
+            });
+            match request_completion(&params.to_string()).await {
+                Ok(text) => text,
+                Err(CompletionError::UnsupportedResponseFormat) => return Err(anyhow::anyhow!("The endpoint rejected the request.")),
+                Err(CompletionError::Other(err)) => return Err(err),
+            }
+        }
+        Err(CompletionError::Other(err)) => return Err(err),
+    };
+
+    extract_json_object(&schema_text).ok_or_else(|| anyhow::anyhow!("No valid JSON found in the returned text."))
+}
+
+/// Sends a single chat completion request and returns the content of the first choice
+async fn request_completion(params: &str) -> Result<String, CompletionError> {
     let mut opts = RequestInit::new();
     let headers = web_sys::Headers::new().unwrap();
 
@@ -93,7 +230,7 @@ pub async fn call_openai(prompt: &str) -> Result<String, anyhow::Error> {
 
     opts.method("POST");
     opts.headers(&headers);
-    opts.body(Some(&JsValue::from_str(&params)));
+    opts.body(Some(&JsValue::from_str(params)));
     opts.mode(RequestMode::Cors);
 
     let request = Request::new_with_str_and_init("https://22vazdmku2qz3prrn57elhdj2i0wyejr.lambda-url.us-west-2.on.aws/", &opts)
@@ -107,27 +244,27 @@ pub async fn call_openai(prompt: &str) -> Result<String, anyhow::Error> {
     
     let response = match response {
         Ok(resp) => resp,
-        Err(err) => return Err(anyhow::anyhow!(err.as_string().unwrap_or("Fetch request failed".to_string()))),
+        Err(err) => return Err(anyhow::anyhow!(err.as_string().unwrap_or("Fetch request failed".to_string())).into()),
     };
 
     let response: Response = match response.dyn_into() {
         Ok(resp) => resp,
-        Err(err) => return Err(anyhow::anyhow!(err.as_string().unwrap_or("Failed to convert JsValue to Response".to_string()))),
+        Err(err) => return Err(anyhow::anyhow!(err.as_string().unwrap_or("Failed to convert JsValue to Response".to_string())).into()),
     };
 
     let text_future = match response.text() {
         Ok(txt_future) => txt_future,
-        Err(_) => return Err(anyhow::anyhow!("Failed to read text from the response")),
+        Err(_) => return Err(anyhow::anyhow!("Failed to read text from the response").into()),
     };
 
     let text_js = match JsFuture::from(text_future).await {
         Ok(txt_js) => txt_js,
-        Err(err) => return Err(anyhow::anyhow!(err.as_string().unwrap_or("Failed to convert future to JsValue".to_string()))),
+        Err(err) => return Err(anyhow::anyhow!(err.as_string().unwrap_or("Failed to convert future to JsValue".to_string())).into()),
     };
 
     let text = match text_js.as_string() {
         Some(txt) => txt,
-        None => return Err(anyhow::anyhow!("Failed to convert JsValue to String")),
+        None => return Err(anyhow::anyhow!("Failed to convert JsValue to String").into()),
     };
 
     // Log to console
@@ -141,27 +278,80 @@ pub async fn call_openai(prompt: &str) -> Result<String, anyhow::Error> {
         } else {
             text
         };
-        return Err(anyhow::anyhow!("HTTP {} error from OpenAI: {}", status, message));
+        if status == 400 && message.contains("response_format") {
+            return Err(CompletionError::UnsupportedResponseFormat);
+        }
+        return Err(anyhow::anyhow!("HTTP {} error from OpenAI: {}", status, message).into());
     }
     
-    let json: serde_json::Value = serde_json::from_str(&text).unwrap();
-    let schema_text = json["choices"][0]["message"]["content"].as_str().unwrap_or("");
+    let json: serde_json::Value = serde_json::from_str(&text)
+        .map_err(|_| anyhow::anyhow!("The response from OpenAI is not valid JSON."))?;
+    Ok(json["choices"][0]["message"]["content"].as_str().unwrap_or("").to_string())
+}
 
-    // Extract the JSON schema from the response
-    let start = schema_text.find('{');
-    let end = schema_text.rfind('}');
+/// Extracts a JSON object from model output. Tries, in order: the whole text, the contents of
+/// fenced code blocks, then every balanced `{...}` span (skipping braces inside strings).
+fn extract_json_object(text: &str) -> Option<String> {
+    let is_object = |candidate: &str| matches!(serde_json::from_str::<Value>(candidate), Ok(Value::Object(_)));
 
-    match (start, end) {
-        (Some(start), Some(end)) => {
-            let schema_json = &schema_text[start..=end];
-            //console::log_1(&JsValue::from_str(schema_json));
-            match serde_json::from_str::<serde_json::Value>(schema_json) {
-                Ok(_) => Ok(schema_json.to_string()),
-                Err(_) => Err(anyhow::anyhow!("Extracted text is not valid JSON.")),
+    let trimmed = text.trim();
+    if is_object(trimmed) {
+        return Some(trimmed.to_string());
+    }
+
+    // Fenced code blocks, with or without a language tag
+    let mut rest = text;
+    while let Some(open) = rest.find("```") {
+        let after_fence = &rest[open + 3..];
+        let body_start = after_fence.find('\n').map(|i| i + 1).unwrap_or(0);
+        let body = &after_fence[body_start..];
+        match body.find("```") {
+            Some(close) => {
+                let block = body[..close].trim();
+                if is_object(block) {
+                    return Some(block.to_string());
+                }
+                rest = &body[close + 3..];
             }
+            None => break,
         }
-        _ => Err(anyhow::anyhow!("No valid JSON found in the returned text.")),
     }
+
+    // Balanced braces, starting from each '{' in turn
+    let bytes = text.as_bytes();
+    for start in text.match_indices('{').map(|(i, _)| i) {
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        for (offset, &byte) in bytes[start..].iter().enumerate() {
+            if in_string {
+                match byte {
+                    _ if escaped => escaped = false,
+                    b'\\' => escaped = true,
+                    b'"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match byte {
+                b'"' => in_string = true,
+                b'{' => depth += 1,
+                b'}' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        let candidate = &text[start..=start + offset];
+                        if is_object(candidate) {
+                            return Some(candidate.to_string());
+                        }
+                        break;
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    None
 }
 
 /// Anything that can be changed with the interface must go in Model
@@ -172,6 +362,9 @@ struct Model {
     /// A vector of document types
     document_types: Vec<DocumentType>,
 
+    /// Contract-level settings, shown above the document types
+    settings: ContractSettings,
+
     /// Each full document type is a single string in json_object
     json_object: Vec<String>,
 
@@ -181,6 +374,9 @@ struct Model {
     /// DPP validation error messages
     error_messages: Vec<String>,
 
+    /// Parts of the last imported contract that the form couldn't represent
+    import_warnings: Vec<String>,
+
 
 
     // OpenAI fields
@@ -194,14 +390,273 @@ struct Model {
     /// Necessary to show a loader while awaiting response
     temp_prompt: Option<String>,
 
-    /// History of prompts
-    history: Vec<String>,
+    /// History of prompts and the contracts they produced
+    history: Vec<HistoryEntry>,
+
+    /// The history entry the current contract was generated from or restored to
+    history_head: Option<usize>,
+
+    /// The history entry currently shown in the preview
+    history_preview: Option<usize>,
 
     /// True while awaiting response
     loading: bool,
 
+    /// Bumped whenever a project is opened, so responses to requests made for the previous one are dropped
+    generation: u64,
+
     /// Error messages from the API
     error_messages_ai: Vec<String>,
+
+
+
+    // Undo/redo
+
+    /// Snapshots taken before each edit, most recent last
+    undo_stack: Vec<Snapshot>,
+
+    /// Snapshots of undone states, most recent last
+    redo_stack: Vec<Snapshot>,
+
+    /// The last text field edited, so consecutive keystrokes in it become a single undo step
+    last_edit: Option<EditKey>,
+
+    /// Ctrl+Z / Ctrl+Shift+Z listener on the window, removed when the component is destroyed
+    shortcut_listener: Option<Closure<dyn Fn(web_sys::KeyboardEvent)>>,
+
+
+
+    // Projects
+
+    /// All projects. The active project's contents live in the fields above and are copied back when saving.
+    workspace: storage::Workspace,
+
+    /// Error from importing a project file
+    project_error: Option<String>,
+
+
+
+    // Contract files
+
+    /// Error from opening or downloading a contract file
+    file_error: Option<String>,
+
+    /// True while a file is dragged over the contract panel
+    drag_over: bool,
+
+    /// Id of a registered contract to load from the network
+    network_contract_id: String,
+
+    /// True while a contract is being fetched from the network
+    network_loading: bool,
+
+    /// Error from loading a contract from the network
+    network_error: Option<String>,
+
+    /// Queries the app will run, checked against the indices
+    queries: Vec<query::Query>,
+
+    /// Queries typed one per line, added to the queries on request
+    query_text: String,
+    query_text_error: Option<String>,
+
+
+
+    // Binary serialization
+
+    /// The contract serialized with Platform's CBOR encoding, with a note on where its ids came from
+    cbor: Option<(Vec<u8>, String)>,
+
+    /// Hex or base64 text to import
+    cbor_input: String,
+
+    /// How `cbor_input` is encoded
+    cbor_input_encoding: serialization::TextEncoding,
+
+    /// Error from serializing or decoding CBOR
+    cbor_error: Option<String>,
+
+
+
+    // Generated code
+
+    /// The kind of code shown in the generated code panel
+    code_format: CodeFormat,
+
+    /// Status of the last copy or download of generated code
+    code_status: Option<String>,
+
+
+
+    // Update compatibility
+
+    /// Document types of the previous, registered version of the contract, compared against the form
+    baseline: Option<Vec<DocumentType>>,
+
+    /// Settings of the previous version, if it was loaded as a full contract
+    baseline_settings: Option<ContractSettings>,
+
+    /// Contract JSON of the previous version to load
+    baseline_input: String,
+
+    /// Error from loading the previous version
+    baseline_error: Option<String>,
+
+    /// Update check errors for the form as an update of the previous version, with the contract JSON they were found for
+    update_check: Option<(Vec<String>, Result<Vec<String>, String>)>,
+
+
+
+    // Structural diff
+
+    /// Where the old and new contracts of the diff come from
+    diff_sources: [DiffSource; 2],
+
+    /// Pasted contract JSON of each side, used when its source is `DiffSource::Pasted`
+    diff_texts: [String; 2],
+
+
+
+    // Registration
+
+    /// Identity id that will own the registered contract
+    owner_id: String,
+
+    /// Whether to build a DataContractCreate or DataContractUpdate transition
+    transition_kind: registration::TransitionKind,
+
+    /// Id of the registered contract to update
+    contract_id: String,
+
+    /// Version of the registered contract to update. The update has the next version.
+    contract_version: String,
+
+    /// Private key of the identity key that signs the transition. Kept in memory only, never saved.
+    private_key: String,
+
+    /// Id of the identity key that signs the transition
+    key_id: String,
+
+    /// The last built state transition
+    transition: Option<registration::Transition>,
+
+    /// Error from building or exporting the transition
+    transition_error: Option<String>,
+
+    /// True while a transition is being broadcast
+    broadcasting: bool,
+
+    /// Outcome of the last broadcast
+    broadcast_status: Option<String>,
+}
+
+/// A contract that can be compared in the structural diff
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DiffSource {
+    Current,
+    Baseline,
+    History(usize),
+    Pasted,
+}
+
+/// Kinds of code generated from the contract
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CodeFormat {
+    Rust,
+    TypeScript,
+    RegistrationScript,
+    Markdown,
+    Html,
+    Mermaid,
+}
+
+impl CodeFormat {
+    const ALL: [CodeFormat; 6] = [
+        CodeFormat::Rust,
+        CodeFormat::TypeScript,
+        CodeFormat::RegistrationScript,
+        CodeFormat::Markdown,
+        CodeFormat::Html,
+        CodeFormat::Mermaid,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            CodeFormat::Rust => "Rust structs",
+            CodeFormat::TypeScript => "TypeScript types",
+            CodeFormat::RegistrationScript => "JS SDK registration script",
+            CodeFormat::Markdown => "Markdown documentation",
+            CodeFormat::Html => "HTML documentation",
+            CodeFormat::Mermaid => "Mermaid ER diagram",
+        }
+    }
+
+    /// File name of the download, given the file stem of the project name
+    fn filename(&self, stem: &str) -> String {
+        match self {
+            CodeFormat::Rust => format!("{}.rs", stem.replace('-', "_")),
+            CodeFormat::TypeScript => format!("{}.ts", stem),
+            CodeFormat::RegistrationScript => format!("register-{}.js", stem),
+            CodeFormat::Markdown => format!("{}.md", stem),
+            CodeFormat::Html => format!("{}.html", stem),
+            CodeFormat::Mermaid => format!("{}.mmd", stem),
+        }
+    }
+
+    fn mime_type(&self) -> &'static str {
+        match self {
+            CodeFormat::Rust => "text/x-rust",
+            CodeFormat::TypeScript => "text/typescript",
+            CodeFormat::RegistrationScript => "text/javascript",
+            CodeFormat::Markdown => "text/markdown",
+            CodeFormat::Html => "text/html",
+            CodeFormat::Mermaid => "text/plain",
+        }
+    }
+}
+
+/// Maximum number of undo steps kept
+const UNDO_LIMIT: usize = 100;
+
+/// The part of Model restored by undo and redo
+#[derive(Clone)]
+struct Snapshot {
+    document_types: Vec<DocumentType>,
+    settings: ContractSettings,
+    json_object: Vec<String>,
+    error_messages: Vec<String>,
+    schema: String,
+}
+
+/// Identifies a single text field: the message variant plus its indices
+#[derive(PartialEq)]
+struct EditKey {
+    kind: std::mem::Discriminant<Msg>,
+    indices: Vec<usize>,
+}
+
+/// How a message interacts with the undo history
+enum UndoKind {
+    /// Doesn't change the contract
+    Ignore,
+    /// A discrete change, always its own undo step
+    Step,
+    /// A keystroke in a text or number field
+    Typing(EditKey),
+}
+
+/// A prompt and a snapshot of the contract it produced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct HistoryEntry {
+    prompt: String,
+    /// The full contract JSON after the prompt was applied, with its settings. Entries saved
+    /// before settings were kept hold only the documents, and restore without touching the settings.
+    schema: String,
+    /// DPP validation error messages for the contract
+    error_messages: Vec<String>,
+    /// The entry this one was generated from. Prompts made after restoring an older
+    /// entry branch from it, so newer entries are kept.
+    parent: Option<usize>,
 }
 
 /// Document type struct
@@ -215,7 +670,13 @@ struct DocumentType {
     created_at_required: bool,
     updated_at_required: bool,
     additionalProperties: bool,
-    comment: String
+    comment: String,
+    #[serde(default)]
+    documents_mutable: Option<bool>,
+    #[serde(default)]
+    can_be_deleted: Option<bool>,
+    #[serde(default)]
+    documents_keep_history: Option<bool>,
 }
 
 impl Default for DocumentType {
@@ -228,8 +689,123 @@ impl Default for DocumentType {
             created_at_required: false,
             updated_at_required: false,
             additionalProperties: false,
-            comment: String::new()
+            comment: String::new(),
+            documents_mutable: None,
+            can_be_deleted: None,
+            documents_keep_history: None,
+        }
+    }
+}
+
+/// Contract-level settings, emitted as the `config` of the full contract alongside its description and keywords
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+struct ContractSettings {
+    readonly: bool,
+    keeps_history: bool,
+    can_be_deleted: bool,
+    documents_mutable_contract_default: bool,
+    documents_keep_history_contract_default: bool,
+    description: String,
+    /// Comma-separated, as typed into the form
+    keywords: String,
+}
+
+impl Default for ContractSettings {
+    fn default() -> Self {
+        Self {
+            readonly: false,
+            keeps_history: false,
+            can_be_deleted: false,
+            documents_mutable_contract_default: true,
+            documents_keep_history_contract_default: false,
+            description: String::new(),
+            keywords: String::new(),
+        }
+    }
+}
+
+/// Limits Platform's meta-schema puts on the contract description and keywords
+const DESCRIPTION_MIN_LENGTH: usize = 3;
+const DESCRIPTION_MAX_LENGTH: usize = 100;
+const KEYWORD_MIN_LENGTH: usize = 3;
+const KEYWORD_MAX_LENGTH: usize = 50;
+const MAX_KEYWORDS: usize = 50;
+
+impl ContractSettings {
+    fn keyword_list(&self) -> Vec<String> {
+        self.keywords.split(',')
+            .map(|keyword| keyword.trim().to_string())
+            .filter(|keyword| !keyword.is_empty())
+            .collect()
+    }
+
+    /// The `config` object passed to DPP and written to the full contract
+    fn config_json(&self) -> Value {
+        json!({
+            "canBeDeleted": self.can_be_deleted,
+            "readonly": self.readonly,
+            "keepsHistory": self.keeps_history,
+            "documentsKeepHistoryContractDefault": self.documents_keep_history_contract_default,
+            "documentsMutableContractDefault": self.documents_mutable_contract_default,
+        })
+    }
+
+    /// The `description` and `keywords` of the full contract, left out when empty.
+    /// The pinned DPP predates both fields and drops them from its CBOR and JSON, so imports carry them over from here.
+    fn metadata_json(&self) -> Map<String, Value> {
+        let mut metadata = Map::new();
+        if !self.description.trim().is_empty() {
+            metadata.insert("description".to_owned(), json!(self.description.trim()));
         }
+        let keywords = self.keyword_list();
+        if !keywords.is_empty() {
+            metadata.insert("keywords".to_owned(), json!(keywords));
+        }
+        metadata
+    }
+
+    /// Reads the settings of a full contract. Missing fields keep their defaults.
+    fn from_contract_json(contract: &Value) -> Self {
+        let mut settings = Self::default();
+        let config = contract.get("config");
+        let flag = |key: &str| config.and_then(|config| config.get(key)).and_then(|value| value.as_bool());
+        settings.can_be_deleted = flag("canBeDeleted").unwrap_or(settings.can_be_deleted);
+        settings.readonly = flag("readonly").unwrap_or(settings.readonly);
+        settings.keeps_history = flag("keepsHistory").unwrap_or(settings.keeps_history);
+        settings.documents_keep_history_contract_default = flag("documentsKeepHistoryContractDefault")
+            .unwrap_or(settings.documents_keep_history_contract_default);
+        settings.documents_mutable_contract_default = flag("documentsMutableContractDefault")
+            .unwrap_or(settings.documents_mutable_contract_default);
+        settings.description = contract.get("description").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        settings.keywords = contract.get("keywords")
+            .and_then(|v| v.as_array())
+            .map(|keywords| keywords.iter().filter_map(|k| k.as_str()).collect::<Vec<_>>().join(", "))
+            .unwrap_or_default();
+        settings
+    }
+
+    /// Checks the description and keywords against Platform's limits. The pinned DPP doesn't know these fields, so nothing else checks them.
+    fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        let description = self.description.trim();
+        if !description.is_empty() && !(DESCRIPTION_MIN_LENGTH..=DESCRIPTION_MAX_LENGTH).contains(&description.chars().count()) {
+            errors.push(format!("Contract settings: the description must be {} to {} characters long.", DESCRIPTION_MIN_LENGTH, DESCRIPTION_MAX_LENGTH));
+        }
+        let keywords = self.keyword_list();
+        if keywords.len() > MAX_KEYWORDS {
+            errors.push(format!("Contract settings: at most {} keywords are allowed.", MAX_KEYWORDS));
+        }
+        for keyword in &keywords {
+            if !(KEYWORD_MIN_LENGTH..=KEYWORD_MAX_LENGTH).contains(&keyword.chars().count()) {
+                errors.push(format!("Contract settings: keyword \"{}\" must be {} to {} characters long.", keyword, KEYWORD_MIN_LENGTH, KEYWORD_MAX_LENGTH));
+            }
+        }
+        let unique: HashSet<String> = keywords.iter().map(|keyword| keyword.to_lowercase()).collect();
+        if unique.len() != keywords.len() {
+            errors.push(String::from("Contract settings: keywords must be unique."));
+        }
+        errors
     }
 }
 
@@ -245,12 +821,17 @@ struct Property {
     max_length: Option<u32>,  // For String data type
     pattern: Option<String>,  // For String data type
     format: Option<String>,   // For String data type
-    minimum: Option<i32>,     // For Integer data type
-    maximum: Option<i32>,     // For Integer data type
+    minimum: Option<Number>,  // For Integer and Number data types
+    maximum: Option<Number>,  // For Integer and Number data types
+    exclusive_minimum: Option<Number>,  // For Integer and Number data types
+    exclusive_maximum: Option<Number>,  // For Integer and Number data types
+    multiple_of: Option<Number>,  // For Integer and Number data types
     byte_array: Option<bool>,  // For Array data type
     min_items: Option<u32>,    // For Array data type
     max_items: Option<u32>,    // For Array data type
     content_media_type: Option<String>,  // For Array data type
+    items: Option<Box<Property>>,  // For Array data type, unless it's a byte array
+    unique_items: Option<bool>,  // For Array data type, unless it's a byte array
     properties: Option<Box<Vec<Property>>>, // For Object data type
     min_properties: Option<u32>, // For Object data type
     max_properties: Option<u32>, // For Object data type
@@ -258,23 +839,123 @@ struct Property {
     additional_properties: Option<bool>, // For Object data type
 }
 
+/// The validation parameters of integer and number properties
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NumericConstraint {
+    Minimum,
+    Maximum,
+    ExclusiveMinimum,
+    ExclusiveMaximum,
+    MultipleOf,
+}
+
+impl NumericConstraint {
+    const ALL: [NumericConstraint; 5] = [
+        NumericConstraint::Minimum,
+        NumericConstraint::Maximum,
+        NumericConstraint::ExclusiveMinimum,
+        NumericConstraint::ExclusiveMaximum,
+        NumericConstraint::MultipleOf,
+    ];
+
+    /// Name of the keyword in the contract
+    fn key(&self) -> &'static str {
+        match self {
+            NumericConstraint::Minimum => "minimum",
+            NumericConstraint::Maximum => "maximum",
+            NumericConstraint::ExclusiveMinimum => "exclusiveMinimum",
+            NumericConstraint::ExclusiveMaximum => "exclusiveMaximum",
+            NumericConstraint::MultipleOf => "multipleOf",
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            NumericConstraint::Minimum => "Minimum ",
+            NumericConstraint::Maximum => "Maximum ",
+            NumericConstraint::ExclusiveMinimum => "Exclusive minimum ",
+            NumericConstraint::ExclusiveMaximum => "Exclusive maximum ",
+            NumericConstraint::MultipleOf => "Multiple of ",
+        }
+    }
+
+    fn get<'a>(&self, property: &'a Property) -> Option<&'a Number> {
+        match self {
+            NumericConstraint::Minimum => property.minimum.as_ref(),
+            NumericConstraint::Maximum => property.maximum.as_ref(),
+            NumericConstraint::ExclusiveMinimum => property.exclusive_minimum.as_ref(),
+            NumericConstraint::ExclusiveMaximum => property.exclusive_maximum.as_ref(),
+            NumericConstraint::MultipleOf => property.multiple_of.as_ref(),
+        }
+    }
+
+    fn set(&self, property: &mut Property, value: Option<Number>) {
+        match self {
+            NumericConstraint::Minimum => property.minimum = value,
+            NumericConstraint::Maximum => property.maximum = value,
+            NumericConstraint::ExclusiveMinimum => property.exclusive_minimum = value,
+            NumericConstraint::ExclusiveMaximum => property.exclusive_maximum = value,
+            NumericConstraint::MultipleOf => property.multiple_of = value,
+        }
+    }
+}
+
+/// Parses a numeric validation parameter: a 64-bit integer for integer properties, a decimal for number properties.
+/// Returns None for text that isn't a valid value.
+fn parse_numeric(data_type: &DataType, text: &str) -> Option<Number> {
+    let text = text.trim();
+    match data_type {
+        // Number inputs also accept whole numbers written like `1e3` or `2.0`
+        DataType::Integer => text.parse::<i64>().ok()
+            .or_else(|| text.parse::<f64>().ok().filter(|v| v.fract() == 0.0 && v.abs() < i64::MAX as f64).map(|v| v as i64))
+            .map(Number::from),
+        _ => text.parse::<f64>().ok().and_then(Number::from_f64),
+    }
+}
+
 /// Index struct
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 struct Index {
     name: String,
     properties: Vec<IndexProperties>,
     unique: bool,
+    /// Whether documents with none of the indexed properties set are indexed. Unset means Platform's default, true.
+    #[serde(default)]
+    null_searchable: Option<bool>,
 }
 
-/// Index properties struct
+/// System fields that can be indexed alongside the document's own properties
+const INDEXABLE_SYSTEM_FIELDS: [&str; 4] = ["$id", "$ownerId", "$createdAt", "$updatedAt"];
+
+/// Index properties struct: a property name, or dotted path, and its sort order
 #[derive(Debug, Clone, Serialize, Deserialize)]
-struct IndexProperties(String, String);
+#[serde(from = "StoredIndexProperties")]
+struct IndexProperties {
+    name: String,
+    order: String,
+}
 
 impl Default for IndexProperties {
     fn default() -> Self {
         Self {
-            0: String::from(""),
-            1: String::from("asc")
+            name: String::from(""),
+            order: String::from("asc")
+        }
+    }
+}
+
+/// Index properties as saved, including the `[name, order]` pairs of drafts saved before they were named
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredIndexProperties {
+    Named { name: String, order: String },
+    Pair(String, String),
+}
+
+impl From<StoredIndexProperties> for IndexProperties {
+    fn from(stored: StoredIndexProperties) -> Self {
+        match stored {
+            StoredIndexProperties::Named { name, order } | StoredIndexProperties::Pair(name, order) => Self { name, order },
         }
     }
 }
@@ -304,11 +985,22 @@ enum Msg {
     AddIndexProperty(usize, usize),
     UpdateName(usize, String),
     UpdateComment(usize, String),
+    UpdateContractReadonly(bool),
+    UpdateContractKeepsHistory(bool),
+    UpdateContractCanBeDeleted(bool),
+    UpdateDocumentsMutableContractDefault(bool),
+    UpdateDocumentsKeepHistoryContractDefault(bool),
+    UpdateContractDescription(String),
+    UpdateContractKeywords(String),
+    UpdateDocumentsMutable(usize, Option<bool>),
+    UpdateCanBeDeleted(usize, Option<bool>),
+    UpdateDocumentsKeepHistory(usize, Option<bool>),
     UpdatePropertyName(usize, usize, String),
     UpdateIndexName(usize, usize, String),
     UpdatePropertyType(usize, usize, Property),
     UpdateIndexUnique(usize, usize, bool),
-    // UpdateIndexSorting(usize, usize, usize, String),
+    UpdateIndexSorting(usize, usize, usize, String),
+    UpdateIndexNullSearchable(usize, usize, bool),
     UpdatePropertyRequired(usize, usize, bool),
     UpdateSystemPropertiesRequired(usize, usize, bool),
     UpdatePropertyDescription(usize, usize, String),
@@ -320,8 +1012,7 @@ enum Msg {
     UpdateStringPropertyMaxLength(usize, usize, Option<u32>),
     UpdateStringPropertyPattern(usize, usize, String),
     UpdateStringPropertyFormat(usize, usize, String),
-    UpdateIntegerPropertyMinimum(usize, usize, Option<i32>),
-    UpdateIntegerPropertyMaximum(usize, usize, Option<i32>),
+    UpdateNumericConstraint(usize, usize, NumericConstraint, Option<Number>),
     //UpdateArrayPropertyByteArray(usize, usize, bool),
     UpdateArrayPropertyMinItems(usize, usize, Option<u32>),
     UpdateArrayPropertyMaxItems(usize, usize, Option<u32>),
@@ -341,8 +1032,7 @@ enum Msg {
     UpdateStringRecPropertyMaxLength(usize, usize, usize, Option<u32>),
     UpdateStringRecPropertyPattern(usize, usize, usize, String),
     UpdateStringRecPropertyFormat(usize, usize, usize, String),
-    UpdateIntegerRecPropertyMaximum(usize, usize, usize, Option<i32>),
-    UpdateIntegerRecPropertyMinimum(usize, usize, usize, Option<i32>),
+    UpdateNumericRecConstraint(usize, usize, usize, NumericConstraint, Option<Number>),
     //UpdateArrayRecPropertyByteArray(usize, usize, usize, bool),
     UpdateArrayRecPropertyMinItems(usize, usize, usize, Option<u32>),
     UpdateArrayRecPropertyMaxItems(usize, usize, usize, Option<u32>),
@@ -350,6 +1040,20 @@ enum Msg {
     UpdateObjectRecPropertyMaxProperties(usize, usize, usize, Option<u32>),
     UpdateObjectRecPropertyMinProperties(usize, usize, usize, Option<u32>),
 
+    // Array items, of a property or of an inner property when the third index is Some
+    UpdateArrayItemsType(usize, usize, Option<usize>, String),
+    UpdateArrayUniqueItems(usize, usize, Option<usize>, bool),
+    UpdateItemsMinLength(usize, usize, Option<usize>, Option<u32>),
+    UpdateItemsMaxLength(usize, usize, Option<usize>, Option<u32>),
+    UpdateItemsPattern(usize, usize, Option<usize>, String),
+    UpdateItemsFormat(usize, usize, Option<usize>, String),
+    UpdateItemsNumericConstraint(usize, usize, Option<usize>, NumericConstraint, Option<Number>),
+    AddItemsProperty(usize, usize, Option<usize>),
+    RemoveItemsProperty(usize, usize, Option<usize>, usize),
+    UpdateItemsPropertyName(usize, usize, Option<usize>, usize, String),
+    UpdateItemsPropertyType(usize, usize, Option<usize>, usize, String),
+    UpdateItemsPropertyRequired(usize, usize, Option<usize>, usize, bool),
+
     // Import
     Import,
     UpdateImportedJson(String),
@@ -358,8 +1062,166 @@ enum Msg {
     // OpenAI
     UpdatePrompt(String),
     GenerateSchema,
-    ReceiveSchema(Result<String, anyhow::Error>),
+    /// Response to a request made in the given project generation
+    ReceiveSchema(u64, Result<String, anyhow::Error>),
     ClearInput,
+
+    // History
+    PreviewHistoryEntry(usize),
+    RestoreHistoryEntry(usize),
+    CloseHistoryPreview,
+
+    // Undo/redo
+    Undo,
+    Redo,
+
+    // Autosave
+    StartFresh,
+
+    // Projects
+    SwitchProject(usize),
+    NewProject,
+    RenameProject(String),
+    DuplicateProject,
+    DeleteProject,
+    ExportProject,
+    OpenProjectFile(web_sys::File),
+    ReceiveProjectFile(Result<String, anyhow::Error>),
+
+    // Contract files
+    DownloadContract,
+    DownloadFullContract,
+    OpenContractFile(web_sys::File),
+    ReceiveContractFile(Result<String, anyhow::Error>),
+    SetDragOver(bool),
+    UpdateNetworkContractId(String),
+    LoadFromNetwork,
+    ReceiveNetworkContract(u64, Result<Vec<u8>, dapi::DapiError>),
+    AddQuery,
+    RemoveQuery(usize),
+    SetQueryDocumentType(usize, String),
+    AddWhereClause(usize),
+    RemoveWhereClause(usize, usize),
+    UpdateWhereField(usize, usize, String),
+    UpdateWhereOperator(usize, usize, query::Operator),
+    AddOrderBy(usize),
+    RemoveOrderBy(usize, usize),
+    UpdateOrderByField(usize, usize, String),
+    UpdateOrderByDirection(usize, usize, bool),
+    UpdateQueryStartAt(usize, String),
+    AddSuggestedIndex(usize),
+    UpdateQueryText(String),
+    ImportQueries,
+    AddSuggestedIndices(String),
+
+    // Binary serialization
+    SerializeCbor,
+    DownloadCbor,
+    UpdateCborInput(String),
+    SetCborInputEncoding(serialization::TextEncoding),
+    ImportCbor,
+    OpenCborFile(web_sys::File),
+    ReceiveCborFile(Result<Vec<u8>, anyhow::Error>),
+
+    // Generated code
+    SetCodeFormat(CodeFormat),
+    CopyCode,
+    DownloadCode,
+    SetCodeStatus(String),
+
+    // Update compatibility
+    UpdateBaselineInput(String),
+    LoadBaseline,
+    OpenBaselineFile(web_sys::File),
+    ReceiveBaselineFile(Result<String, anyhow::Error>),
+    UseCurrentAsBaseline,
+    ClearBaseline,
+    CheckUpdate,
+
+    // Structural diff
+    SetDiffSource(usize, DiffSource),
+    UpdateDiffText(usize, String),
+
+    // Registration
+    UpdateOwnerId(String),
+    SetTransitionKind(registration::TransitionKind),
+    UpdateContractId(String),
+    UpdateContractVersion(String),
+    UpdatePrivateKey(String),
+    UpdateKeyId(String),
+    BuildTransition,
+    SignTransition,
+    DownloadTransitionJson,
+    DownloadTransitionBytes,
+    UpdateDapiEndpoint(String),
+    Broadcast,
+    ReceiveBroadcastResult(u64, Result<(), dapi::DapiError>),
+}
+
+impl Msg {
+    /// Classifies the message for the undo history
+    fn undo_kind(&self) -> UndoKind {
+        use Msg::*;
+        let typing = |indices: &[usize]| UndoKind::Typing(EditKey { kind: std::mem::discriminant(self), indices: indices.to_vec() });
+        match self {
+            UpdateContractDescription(_) | UpdateContractKeywords(_) => typing(&[]),
+            UpdateName(d, _) | UpdateComment(d, _) => typing(&[*d]),
+            UpdatePropertyName(d, p, _) | UpdateIndexName(d, p, _) | UpdatePropertyDescription(d, p, _) | UpdatePropertyComment(d, p, _)
+            | UpdateStringPropertyMinLength(d, p, _) | UpdateStringPropertyMaxLength(d, p, _) | UpdateStringPropertyPattern(d, p, _)
+            | UpdateStringPropertyFormat(d, p, _)
+            | UpdateArrayPropertyMinItems(d, p, _) | UpdateArrayPropertyMaxItems(d, p, _) | UpdateArrayPropertyCMT(d, p, _)
+            | UpdateObjectPropertyMinProperties(d, p, _) | UpdateObjectPropertyMaxProperties(d, p, _) => typing(&[*d, *p]),
+            UpdateNumericConstraint(d, p, c, _) => typing(&[*d, *p, *c as usize]),
+            UpdateNumericRecConstraint(d, p, r, c, _) => typing(&[*d, *p, *r, *c as usize]),
+            UpdateRecPropertyName(d, p, r, _) | UpdateRecPropertyDescription(d, p, r, _)
+            | UpdateRecPropertyComment(d, p, r, _) | UpdateStringRecPropertyMinLength(d, p, r, _) | UpdateStringRecPropertyMaxLength(d, p, r, _)
+            | UpdateStringRecPropertyPattern(d, p, r, _) | UpdateStringRecPropertyFormat(d, p, r, _)
+            | UpdateArrayRecPropertyMinItems(d, p, r, _) | UpdateArrayRecPropertyMaxItems(d, p, r, _)
+            | UpdateArrayRecPropertyCMT(d, p, r, _) | UpdateObjectRecPropertyMaxProperties(d, p, r, _)
+            | UpdateObjectRecPropertyMinProperties(d, p, r, _) => typing(&[*d, *p, *r]),
+            UpdateItemsMinLength(d, p, r, _) | UpdateItemsMaxLength(d, p, r, _) | UpdateItemsPattern(d, p, r, _)
+            | UpdateItemsFormat(d, p, r, _) => typing(&[*d, *p, r.unwrap_or(usize::MAX)]),
+            UpdateItemsNumericConstraint(d, p, r, c, _) => typing(&[*d, *p, r.unwrap_or(usize::MAX), *c as usize]),
+            UpdateItemsPropertyName(d, p, r, i, _) => typing(&[*d, *p, r.unwrap_or(usize::MAX), *i]),
+            ReceiveSchema(_, Err(_)) | UpdateImportedJson(_) | UpdatePrompt(_) | GenerateSchema | ClearInput
+            | PreviewHistoryEntry(_) | CloseHistoryPreview | Undo | Redo | StartFresh
+            | SwitchProject(_) | NewProject | RenameProject(_) | DuplicateProject | DeleteProject | ExportProject
+            | OpenProjectFile(_) | ReceiveProjectFile(_) | DownloadContract | DownloadFullContract | OpenContractFile(_) | ReceiveContractFile(_)
+            | SetDragOver(_) | SerializeCbor | DownloadCbor | UpdateCborInput(_) | SetCborInputEncoding(_) | ImportCbor
+            | OpenCborFile(_) | ReceiveCborFile(_) | SetCodeFormat(_) | CopyCode | DownloadCode | SetCodeStatus(_) | UpdateBaselineInput(_)
+            | LoadBaseline | OpenBaselineFile(_) | ReceiveBaselineFile(_) | UseCurrentAsBaseline | ClearBaseline | CheckUpdate
+            | SetDiffSource(..) | UpdateDiffText(..) | UpdateOwnerId(_) | SetTransitionKind(_) | UpdateContractId(_)
+            | UpdateContractVersion(_) | UpdatePrivateKey(_) | UpdateKeyId(_) | BuildTransition | SignTransition
+            | DownloadTransitionJson | DownloadTransitionBytes | UpdateDapiEndpoint(_) | Broadcast
+            | ReceiveBroadcastResult(..) | UpdateNetworkContractId(_) | LoadFromNetwork | ReceiveNetworkContract(..)
+            | AddQuery | RemoveQuery(_) | SetQueryDocumentType(..) | AddWhereClause(_) | RemoveWhereClause(..)
+            | UpdateWhereField(..) | UpdateWhereOperator(..) | AddOrderBy(_) | RemoveOrderBy(..) | UpdateOrderByField(..)
+            | UpdateOrderByDirection(..) | UpdateQueryStartAt(..) | UpdateQueryText(_) | ImportQueries => UndoKind::Ignore,
+            _ => UndoKind::Step,
+        }
+    }
+
+    /// Whether the message can change what is autosaved: the open project's draft or the workspace.
+    /// Saving serializes every project, so UI-only messages such as typing a private key or dragging a file skip it.
+    fn changes_saved_state(&self) -> bool {
+        use Msg::*;
+        !matches!(self,
+            UpdatePrompt(_) | PreviewHistoryEntry(_) | CloseHistoryPreview | ExportProject | OpenProjectFile(_)
+            | DownloadContract | DownloadFullContract | OpenContractFile(_) | SetDragOver(_) | UpdateNetworkContractId(_)
+            | LoadFromNetwork | UpdateQueryText(_) | SerializeCbor | DownloadCbor | UpdateCborInput(_)
+            | SetCborInputEncoding(_) | ImportCbor | OpenCborFile(_) | ReceiveCborFile(_) | SetCodeFormat(_) | CopyCode | DownloadCode | SetCodeStatus(_)
+            | UpdateBaselineInput(_) | OpenBaselineFile(_) | CheckUpdate | SetDiffSource(..) | UpdateDiffText(..)
+            | SetTransitionKind(_) | UpdatePrivateKey(_) | UpdateKeyId(_) | BuildTransition | SignTransition
+            | DownloadTransitionJson | DownloadTransitionBytes | Broadcast)
+    }
+
+    /// The project generation an async response belongs to
+    fn request_generation(&self) -> Option<u64> {
+        match self {
+            Msg::ReceiveSchema(generation, _) | Msg::ReceiveNetworkContract(generation, _) | Msg::ReceiveBroadcastResult(generation, _) => Some(*generation),
+            _ => None,
+        }
+    }
 }
 
 /// Sets the validation parameters to default. Used to reset the fields when a 
@@ -409,6 +1271,740 @@ fn default_additional_properties(data_type: &str) -> Property {
 // Contains functions that generate the webpage and json object
 impl Model {
 
+    /// The contents of the active project
+    fn to_draft(&self) -> storage::Draft {
+        storage::Draft {
+            document_types: self.document_types.clone(),
+            settings: self.settings.clone(),
+            json_object: self.json_object.clone(),
+            error_messages: self.error_messages.clone(),
+            imported_json: self.imported_json.clone(),
+            schema: self.schema.clone(),
+            history: self.history.clone(),
+            history_head: self.history_head,
+            baseline: self.baseline.clone(),
+            baseline_settings: self.baseline_settings.clone(),
+            owner_id: self.owner_id.clone(),
+            contract_id: self.contract_id.clone(),
+            contract_version: self.contract_version.clone(),
+            queries: self.queries.clone(),
+        }
+    }
+
+    fn apply_draft(&mut self, draft: storage::Draft) {
+        self.document_types = draft.document_types;
+        self.settings = draft.settings;
+        self.json_object = draft.json_object;
+        self.error_messages = draft.error_messages;
+        self.imported_json = draft.imported_json;
+        self.schema = draft.schema;
+        self.history = draft.history;
+        self.history_head = draft.history_head;
+        self.history_preview = None;
+        self.baseline = draft.baseline;
+        self.baseline_settings = draft.baseline_settings;
+        self.owner_id = draft.owner_id;
+        self.contract_id = draft.contract_id;
+        self.contract_version = draft.contract_version;
+        self.queries = draft.queries;
+    }
+
+    /// Copies the active project back into the workspace and autosaves it
+    fn save(&mut self) {
+        self.workspace.projects[self.workspace.active].draft = self.to_draft();
+        storage::save_workspace(&self.workspace);
+    }
+
+    /// Opens a project. The current project must have been saved first.
+    fn open_project(&mut self, index: usize) {
+        self.workspace.active = index;
+        let draft = self.workspace.projects[index].draft.clone();
+        self.apply_draft(draft);
+        self.prompt.clear();
+        self.import_warnings.clear();
+        self.project_error = None;
+        self.baseline_input.clear();
+        self.baseline_error = None;
+        self.update_check = None;
+        self.transition = None;
+        self.transition_error = None;
+        self.broadcast_status = None;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.last_edit = None;
+        self.generation = self.generation.wrapping_add(1);
+        self.loading = false;
+        self.temp_prompt = None;
+        self.network_loading = false;
+        self.network_error = None;
+        self.broadcasting = false;
+    }
+
+    fn view_projects(&self, ctx: &yew::Context<Self>) -> Html {
+        let active = self.workspace.active;
+        html! {
+            <div class="project-bar">
+                <div class="project-select">
+                    <label>{"Project"}</label>
+                    <select onchange={ctx.link().callback(|e: Event| Msg::SwitchProject(e.target_dyn_into::<HtmlSelectElement>().unwrap().selected_index().max(0) as usize))}>
+                        {for self.workspace.projects.iter().enumerate().map(|(i, project)| html! {
+                            <option value={i.to_string()} selected={i == active}>{project.name.clone()}</option>
+                        })}
+                    </select>
+                </div>
+                <div class="project-name">
+                    <label>{"Name"}</label>
+                    <input type="text" class="text-field"
+                        value={self.workspace.projects[active].name.clone()}
+                        oninput={ctx.link().callback(|e: InputEvent| Msg::RenameProject(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))}
+                    />
+                </div>
+                <div class="project-actions">
+                    <button class="button-clear" onclick={ctx.link().callback(|_| Msg::NewProject)}>{"New"}</button>
+                    <button class="button-clear" onclick={ctx.link().callback(|_| Msg::DuplicateProject)}>{"Duplicate"}</button>
+                    <button class="button-clear" onclick={ctx.link().callback(|_| Msg::DeleteProject)}>{"Delete"}</button>
+                    <button class="button-clear" onclick={ctx.link().callback(|_| Msg::ExportProject)}>{"Export"}</button>
+                    <label class="button-clear file-button">{"Import"}
+                        <input type="file" accept=".json,application/json" onchange={ctx.link().batch_callback(|e: Event| {
+                            let input = e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap();
+                            let file = input.files().and_then(|files| files.get(0));
+                            input.set_value("");
+                            file.map(Msg::OpenProjectFile)
+                        })} />
+                    </label>
+                </div>
+                {if let Some(error) = &self.project_error {
+                    html! {<p class="error-text">{error.clone()}</p>}
+                } else { html! {} }}
+            </div>
+        }
+    }
+
+    /// The current contract with its settings: `config`, `description`, `keywords` and `documents`
+    fn full_contract_json(&self) -> Value {
+        let documents: Value = serde_json::from_str(&format!("{{{}}}", self.json_object.join(","))).unwrap_or_else(|_| json!({}));
+        let mut contract = Map::new();
+        contract.insert("config".to_owned(), self.settings.config_json());
+        contract.extend(self.settings.metadata_json());
+        contract.insert("documents".to_owned(), documents);
+        Value::Object(contract)
+    }
+
+    /// The current contract, pretty-printed
+    fn contract_json_pretty(&self) -> String {
+        let s = format!("{{{}}}", self.json_object.join(","));
+        serde_json::from_str::<Value>(&s)
+            .and_then(|value| serde_json::to_string_pretty(&value))
+            .unwrap_or(s)
+    }
+
+    fn view_network_import(&self, ctx: &yew::Context<Self>) -> Html {
+        html! {
+            <div class="network-section">
+                <h3>{"Load from network"}</h3>
+                <label>{"Contract id"}</label>
+                <input type="text" placeholder="Base58 or hex" value={self.network_contract_id.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateNetworkContractId(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                <label>{"DAPI endpoint"}</label>
+                <input type="text" placeholder="gRPC-web URL, e.g. https://127.0.0.1:3000" value={self.workspace.dapi_endpoint.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateDapiEndpoint(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                <div class="button-block">
+                    <button class="button-import" disabled={self.network_contract_id.trim().is_empty() || self.network_loading} onclick={ctx.link().callback(|_| Msg::LoadFromNetwork)}>
+                        {if self.network_loading { "Loading..." } else { "Load" }}
+                    </button>
+                </div>
+                <p>{"The loaded contract replaces the form and becomes the previous version for the update compatibility check."}</p>
+                {if let Some(error) = &self.network_error {
+                    html! {<p class="error-text">{error.clone()}</p>}
+                } else { html! {} }}
+            </div>
+        }
+    }
+
+    fn view_cbor(&self, ctx: &yew::Context<Self>) -> Html {
+        html! {
+            <div class="cbor-section">
+                <h3>{"Binary serialization (CBOR)"}</h3>
+                <div class="button-block">
+                    <button class="button-clear wide" disabled={self.json_object.is_empty()} onclick={ctx.link().callback(|_| Msg::SerializeCbor)}>{"Serialize"}</button>
+                    <button class="button-clear wide" disabled={self.cbor.is_none()} onclick={ctx.link().callback(|_| Msg::DownloadCbor)}>{"Download .cbor"}</button>
+                </div>
+                {if let Some((bytes, ids)) = &self.cbor {
+                    html! {
+                        <>
+                            <p>{format!("Size: {} bytes. {}", bytes.len(), ids)}</p>
+                            <label>{"Hex"}</label>
+                            <textarea class="textarea-no-whitespace" readonly=true value={serialization::to_hex(bytes)}></textarea>
+                            <label>{"Base64"}</label>
+                            <textarea class="textarea-no-whitespace" readonly=true value={serialization::to_base64(bytes)}></textarea>
+                        </>
+                    }
+                } else { html! {} }}
+                <label>{"Import from hex or base64"}</label>
+                <select onchange={ctx.link().callback(|e: Event| {
+                    let index = e.target_dyn_into::<HtmlSelectElement>().unwrap().selected_index().max(0) as usize;
+                    Msg::SetCborInputEncoding(serialization::TextEncoding::ALL[index.min(serialization::TextEncoding::ALL.len() - 1)])
+                })}>
+                    {for serialization::TextEncoding::ALL.iter().map(|encoding| html! {
+                        <option selected={*encoding == self.cbor_input_encoding}>{encoding.label()}</option>
+                    })}
+                </select>
+                <textarea class="textarea-no-whitespace" value={self.cbor_input.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateCborInput(e.target_dyn_into::<web_sys::HtmlTextAreaElement>().unwrap().value()))}></textarea>
+                <div class="button-block">
+                    <label class="button-clear wide file-button">{"Open .cbor file"}
+                        <input type="file" accept=".cbor,.bin,application/cbor,application/octet-stream" onchange={ctx.link().batch_callback(|e: Event| {
+                            let input = e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap();
+                            let file = input.files().and_then(|files| files.get(0));
+                            input.set_value("");
+                            file.map(Msg::OpenCborFile)
+                        })} />
+                    </label>
+                    <button class="button-import" disabled={self.cbor_input.trim().is_empty()} onclick={ctx.link().callback(|_| Msg::ImportCbor)}>{"Import CBOR"}</button>
+                </div>
+                {if let Some(error) = &self.cbor_error {
+                    html! {<p class="error-text">{error.clone()}</p>}
+                } else { html! {} }}
+            </div>
+        }
+    }
+
+    /// Code in the selected format, generated from the document types in the form
+    fn generated_code(&self) -> String {
+        match self.code_format {
+            CodeFormat::Rust => codegen::rust_module(&self.document_types),
+            CodeFormat::TypeScript => codegen::typescript_types(&self.document_types),
+            CodeFormat::RegistrationScript => codegen::registration_script(&self.contract_json_pretty()),
+            CodeFormat::Markdown => docs::markdown(&self.workspace.projects[self.workspace.active].name, &self.document_types),
+            CodeFormat::Html => docs::html(&self.workspace.projects[self.workspace.active].name, &self.document_types),
+            CodeFormat::Mermaid => diagram::mermaid(&self.document_types),
+        }
+    }
+
+    fn view_compatibility(&self, ctx: &yew::Context<Self>) -> Html {
+        let report = match &self.baseline {
+            Some(baseline) => {
+                let mut changes = self.baseline_settings.as_ref()
+                    .map(|settings| diff::diff_settings(settings, &self.settings))
+                    .unwrap_or_default();
+                changes.extend(diff::diff(baseline, &self.document_types));
+                let rejected = changes.iter().filter(|change| change.update_rejection(baseline, &self.document_types).is_some()).count();
+                let readonly = self.baseline_settings.as_ref().map_or(false, |settings| settings.readonly);
+                html! {
+                    <>
+                        {if readonly && !changes.is_empty() {
+                            html! {<p class="error-text">{"The previous version is read-only, so Platform rejects any update to it."}</p>}
+                        } else { html! {} }}
+                        <p>{if changes.is_empty() {
+                            String::from("No changes from the previous version.")
+                        } else if rejected == 0 {
+                            format!("{} changes, none of them against Platform's usual update rules. Check the update with DPP for a closer look.", changes.len())
+                        } else {
+                            format!("{} changes, {} of them likely to be rejected in a contract update. Check the update with DPP for a closer look.", changes.len(), rejected)
+                        }}</p>
+                        <ul class="compatibility-changes">
+                            {for changes.iter().map(|change| match change.update_rejection(baseline, &self.document_types) {
+                                Some(reason) => html! {<li class="error-text">{format!("{} ({})", change.description(), reason)}</li>},
+                                None => html! {<li>{change.description()}</li>},
+                            })}
+                        </ul>
+                    </>
+                }
+            },
+            None => html! {<p>{"Load the registered version of the contract to check whether the form's changes are a valid update."}</p>},
+        };
+        html! {
+            <div class="compatibility-section">
+                <h3>{"Update compatibility"}</h3>
+                <label>{"Previous version"}</label>
+                <textarea class="textarea-no-whitespace" value={self.baseline_input.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateBaselineInput(e.target_dyn_into::<web_sys::HtmlTextAreaElement>().unwrap().value()))}></textarea>
+                <div class="button-block">
+                    <button class="button-import" disabled={self.baseline_input.trim().is_empty()} onclick={ctx.link().callback(|_| Msg::LoadBaseline)}>{"Load"}</button>
+                    <label class="button-clear wide file-button">{"Open file"}
+                        <input type="file" accept=".json,application/json" onchange={ctx.link().batch_callback(|e: Event| {
+                            let input = e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap();
+                            let file = input.files().and_then(|files| files.get(0));
+                            input.set_value("");
+                            file.map(Msg::OpenBaselineFile)
+                        })} />
+                    </label>
+                </div>
+                <div class="button-block">
+                    <button class="button-clear wide" title="Compare later edits against the form as it is now" onclick={ctx.link().callback(|_| Msg::UseCurrentAsBaseline)}>{"Use current form"}</button>
+                    <button class="button-clear wide" disabled={self.baseline.is_none()} onclick={ctx.link().callback(|_| Msg::ClearBaseline)}>{"Clear"}</button>
+                </div>
+                {if let Some(error) = &self.baseline_error {
+                    html! {<p class="error-text">{error.clone()}</p>}
+                } else { html! {} }}
+                {report}
+                {if self.baseline.is_some() {
+                    html! {
+                        <div class="button-block">
+                            <button class="button-clear wide" title="Check the config and document types, and run DPP's schema and index compatibility checks against the previous version" onclick={ctx.link().callback(|_| Msg::CheckUpdate)}>{"Check update with DPP"}</button>
+                        </div>
+                    }
+                } else { html! {} }}
+                {match &self.update_check {
+                    Some((source, _)) if *source != self.json_object => html! {<p>{"The contract changed since the last DPP check. Check it again."}</p>},
+                    Some((_, Ok(errors))) if errors.is_empty() => html! {<p class="passed-text">{"The update keeps the config and document types, and passes DPP's schema and index compatibility checks ✓"}</p>},
+                    Some((_, Ok(errors))) => html! {
+                        <ul class="compatibility-changes">
+                            {for errors.iter().map(|error| html! {<li class="error-text">{error.clone()}</li>})}
+                        </ul>
+                    },
+                    Some((_, Err(error))) => html! {<p class="error-text">{error.clone()}</p>},
+                    None => html! {},
+                }}
+            </div>
+        }
+    }
+
+    fn view_registration(&self, ctx: &yew::Context<Self>) -> Html {
+        let transition = match &self.transition {
+            Some(transition) => {
+                let exported = transition.to_json().and_then(|json| Ok((json, transition.to_bytes()?)));
+                match exported {
+                    Ok((json, bytes)) => html! {
+                        <>
+                            {if transition.source != self.json_object {
+                                html! {<p class="error-text">{"The contract changed since the transition was built. Build it again."}</p>}
+                            } else { html! {} }}
+                            <p>{format!("{} for contract {}. Size: {} bytes, {}.", transition.kind().label(), transition.contract_id(), bytes.len(),
+                                transition.signed_by.map_or(String::from("unsigned"), |key_id| format!("signed with key {}", key_id)))}</p>
+                            <label>{"Identity private key"}</label>
+                            <input type="password" placeholder="WIF or hex, kept in memory only" autocomplete="off" value={self.private_key.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdatePrivateKey(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                            <label>{"Key id"}</label>
+                            <input type="text" placeholder="0" value={self.key_id.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateKeyId(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                            <div class="button-block">
+                                <button class="button-import" disabled={self.private_key.trim().is_empty()} onclick={ctx.link().callback(|_| Msg::SignTransition)}>{"Sign"}</button>
+                            </div>
+                            <label>{"JSON"}</label>
+                            <textarea class="textarea-whitespace code" readonly=true value={json}></textarea>
+                            <label>{"Hex"}</label>
+                            <textarea class="textarea-no-whitespace" readonly=true value={serialization::to_hex(&bytes)}></textarea>
+                            <label>{"Base64"}</label>
+                            <textarea class="textarea-no-whitespace" readonly=true value={serialization::to_base64(&bytes)}></textarea>
+                            <div class="button-block">
+                                <button class="button-clear wide" onclick={ctx.link().callback(|_| Msg::DownloadTransitionJson)}>{"Download JSON"}</button>
+                                <button class="button-clear wide" onclick={ctx.link().callback(|_| Msg::DownloadTransitionBytes)}>{"Download .bin"}</button>
+                            </div>
+                            <label>{"DAPI endpoint"}</label>
+                            <input type="text" placeholder="gRPC-web URL, e.g. https://127.0.0.1:3000" value={self.workspace.dapi_endpoint.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateDapiEndpoint(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                            <div class="button-block">
+                                <button class="button-import" disabled={transition.signed_by.is_none() || transition.source != self.json_object || self.broadcasting} onclick={ctx.link().callback(|_| Msg::Broadcast)}>
+                                    {if self.broadcasting { "Broadcasting..." } else { "Broadcast" }}
+                                </button>
+                            </div>
+                            {if let Some(status) = &self.broadcast_status {
+                                html! {<p>{status.clone()}</p>}
+                            } else { html! {} }}
+                        </>
+                    },
+                    Err(err) => html! {<p class="error-text">{err.to_string()}</p>},
+                }
+            },
+            None => html! {},
+        };
+        html! {
+            <div class="registration-section">
+                <h3>{"Registration"}</h3>
+                <p>{"Builds the state transition that registers or updates the contract, to sign here or with your own tooling."}</p>
+                <select onchange={ctx.link().callback(|e: Event| {
+                    let index = e.target_dyn_into::<HtmlSelectElement>().unwrap().selected_index().max(0) as usize;
+                    Msg::SetTransitionKind(registration::TransitionKind::ALL[index.min(registration::TransitionKind::ALL.len() - 1)])
+                })}>
+                    {for registration::TransitionKind::ALL.iter().map(|kind| html! {
+                        <option selected={*kind == self.transition_kind}>{kind.label()}</option>
+                    })}
+                </select>
+                <label>{"Owner identity id"}</label>
+                <input type="text" placeholder="Base58 or hex" value={self.owner_id.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateOwnerId(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                {if self.transition_kind == registration::TransitionKind::Update {
+                    html! {
+                        <>
+                            <label>{"Registered contract id"}</label>
+                            <input type="text" placeholder="Base58 or hex" value={self.contract_id.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateContractId(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                            <label>{"Registered version"}</label>
+                            <input type="text" placeholder="1" value={self.contract_version.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateContractVersion(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                        </>
+                    }
+                } else { html! {} }}
+                <div class="button-block">
+                    <button class="button-import" disabled={self.json_object.is_empty()} onclick={ctx.link().callback(|_| Msg::BuildTransition)}>{"Build transition"}</button>
+                </div>
+                {if let Some(error) = &self.transition_error {
+                    html! {<p class="error-text">{error.clone()}</p>}
+                } else { html! {} }}
+                {transition}
+            </div>
+        }
+    }
+
+    /// The contracts available to the structural diff, with their labels
+    fn diff_source_options(&self) -> Vec<(DiffSource, String)> {
+        let mut options = vec![
+            (DiffSource::Current, String::from("Current form")),
+            (DiffSource::Baseline, String::from("Previous version")),
+        ];
+        options.extend(self.history.iter().enumerate().map(|(i, entry)| (DiffSource::History(i), format!("v{}: {}", i+1, entry.prompt))));
+        options.push((DiffSource::Pasted, String::from("Pasted JSON")));
+        options
+    }
+
+    /// Document types of one side of the structural diff
+    fn diff_document_types(&self, side: usize) -> Result<Vec<DocumentType>, String> {
+        match self.diff_sources[side] {
+            DiffSource::Current => Ok(self.document_types.clone()),
+            DiffSource::Baseline => self.baseline.clone().ok_or_else(|| String::from("No previous version loaded. Load one under \"Update compatibility\".")),
+            DiffSource::History(i) => {
+                let entry = self.history.get(i).ok_or_else(|| String::from("History entry not found"))?;
+                Self::parse_contract_documents(&entry.schema).map(|documents| Self::parse_document_types(documents, &mut Vec::new()))
+            },
+            DiffSource::Pasted if self.diff_texts[side].trim().is_empty() => Err(String::from("Paste a contract to compare")),
+            DiffSource::Pasted => Self::parse_contract_documents(&self.diff_texts[side]).map(|documents| Self::parse_document_types(documents, &mut Vec::new())),
+        }
+    }
+
+    fn view_diff_side(&self, ctx: &yew::Context<Self>, side: usize, options: &[(DiffSource, String)]) -> Html {
+        let selected = self.diff_sources[side];
+        let sources: Vec<DiffSource> = options.iter().map(|(source, _)| *source).collect();
+        html! {
+            <div class="diff-side">
+                <label>{if side == 0 { "Old" } else { "New" }}</label>
+                <select onchange={ctx.link().callback(move |e: Event| {
+                    let index = e.target_dyn_into::<HtmlSelectElement>().unwrap().selected_index().max(0) as usize;
+                    Msg::SetDiffSource(side, sources[index.min(sources.len() - 1)])
+                })}>
+                    {for options.iter().map(|(source, label)| html! {
+                        <option selected={*source == selected}>{label.clone()}</option>
+                    })}
+                </select>
+                {if selected == DiffSource::Pasted {
+                    html! {
+                        <textarea class="textarea-no-whitespace" value={self.diff_texts[side].clone()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateDiffText(side, e.target_dyn_into::<web_sys::HtmlTextAreaElement>().unwrap().value()))}></textarea>
+                    }
+                } else { html! {} }}
+            </div>
+        }
+    }
+
+    fn view_diff(&self, ctx: &yew::Context<Self>) -> Html {
+        let options = self.diff_source_options();
+        let result = match (self.diff_document_types(0), self.diff_document_types(1)) {
+            (Ok(old), Ok(new)) => {
+                let changes = diff::diff(&old, &new);
+                if changes.is_empty() {
+                    html! {<p>{"The contracts are structurally identical."}</p>}
+                } else {
+                    html! {
+                        <table class="diff-table">
+                            <tr><th>{"Document type"}</th><th>{"Change"}</th><th>{"Old"}</th><th>{"New"}</th></tr>
+                            {for changes.iter().map(|change| {
+                                let (old, new) = change.sides();
+                                html! {
+                                    <tr class={format!("diff-{}", change.status())}>
+                                        <td>{change.document_type.clone()}</td>
+                                        <td>{change.label()}</td>
+                                        <td>{old}</td>
+                                        <td>{new}</td>
+                                    </tr>
+                                }
+                            })}
+                        </table>
+                    }
+                }
+            },
+            (Err(err), _) | (_, Err(err)) => html! {<p class="error-text">{err}</p>},
+        };
+        html! {
+            <details class="diff-section">
+                <summary>{"Compare contracts"}</summary>
+                <div class="diff-sides">
+                    {self.view_diff_side(ctx, 0, &options)}
+                    {self.view_diff_side(ctx, 1, &options)}
+                </div>
+                {result}
+            </details>
+        }
+    }
+
+    /// A select of the fields a query can use
+    fn view_query_field(&self, paths: &[String], selected: &str, onchange: Callback<Event>) -> Html {
+        let unknown = !selected.is_empty() && !paths.iter().any(|path| path == selected);
+        html! {
+            <select onchange={onchange}>
+                <option value="" selected={selected.is_empty()} disabled=true>{"Field"}</option>
+                if unknown {
+                    <option value={selected.to_string()} selected=true>{format!("{} (not a field)", selected)}</option>
+                }
+                {for paths.iter().map(|path| html! {
+                    <option value={path.clone()} selected={path == selected}>{path.clone()}</option>
+                })}
+            </select>
+        }
+    }
+
+    fn view_query(&self, ctx: &yew::Context<Self>, index: usize) -> Html {
+        let query = &self.queries[index];
+        let document_type = self.document_types.iter().find(|document_type| document_type.name == query.document_type);
+        let paths = document_type.map(indexing::indexable_paths).unwrap_or_default();
+        let names: Vec<String> = self.document_types.iter().map(|document_type| document_type.name.clone()).collect();
+        let result = match document_type.map(|document_type| (document_type, query::plan(document_type, query))) {
+            None => html! {<p class="error-text">{format!("There is no document type named \"{}\".", query.document_type)}</p>},
+            Some((_, query::Plan::Invalid(errors))) => html! {
+                <ul class="error-text">{for errors.into_iter().map(|error| html! {<li>{error}</li>})}</ul>
+            },
+            Some((_, query::Plan::ById)) => html! {<p class="passed-text">{"Served without an index, in $id order ✓"}</p>},
+            Some((document_type, query::Plan::Index(position))) => {
+                let index = &document_type.indices[position];
+                let fields: Vec<&str> = index.properties.iter().map(|property| property.name.as_str()).collect();
+                html! {<p class="passed-text">{format!("Served by index \"{}\" ({}) ✓", index.name, fields.join(", "))}</p>}
+            },
+            Some((document_type, query::Plan::Missing(suggested))) => {
+                let fields: Vec<&str> = suggested.properties.iter().map(|property| property.name.as_str()).collect();
+                let problems: Vec<String> = fields.iter().filter_map(|field| indexing::index_property_problem(document_type, field)).collect();
+                html! {
+                    <>
+                        <p class="error-text">{"No index serves this query."}</p>
+                        <p>{format!("Suggested index \"{}\": {}", suggested.name, fields.join(", "))}</p>
+                        {for problems.into_iter().map(|problem| html! {<p class="error-text">{problem}</p>})}
+                        <button class="button-clear wide" onclick={ctx.link().callback(move |_| Msg::AddSuggestedIndex(index))}>{"Add index"}</button>
+                    </>
+                }
+            },
+        };
+        html! {
+            <div class="query">
+                <div class="query-line">
+                    <select onchange={ctx.link().callback(move |e: Event| Msg::SetQueryDocumentType(index, e.target_dyn_into::<HtmlSelectElement>().unwrap().value()))}>
+                        {for names.iter().map(|name| html! {
+                            <option value={name.clone()} selected={*name == query.document_type}>{name.clone()}</option>
+                        })}
+                    </select>
+                    <button class="button remove" onclick={ctx.link().callback(move |_| Msg::RemoveQuery(index))}><img src="https://media.hellar.io/wp-content/uploads/trash-icon.svg"/></button>
+                </div>
+                <label>{"Where"}</label>
+                {for query.where_clauses.iter().enumerate().map(|(clause_index, clause)| html! {
+                    <div class="query-line">
+                        {self.view_query_field(&paths, &clause.field, ctx.link().callback(move |e: Event| Msg::UpdateWhereField(index, clause_index, e.target_dyn_into::<HtmlSelectElement>().unwrap().value())))}
+                        <select onchange={ctx.link().callback(move |e: Event| {
+                            let selected = e.target_dyn_into::<HtmlSelectElement>().unwrap().selected_index().max(0) as usize;
+                            Msg::UpdateWhereOperator(index, clause_index, query::Operator::ALL[selected.min(query::Operator::ALL.len() - 1)])
+                        })}>
+                            {for query::Operator::ALL.iter().map(|operator| html! {
+                                <option selected={*operator == clause.operator}>{operator.symbol()}</option>
+                            })}
+                        </select>
+                        <button class="button remove" onclick={ctx.link().callback(move |_| Msg::RemoveWhereClause(index, clause_index))}><img src="https://media.hellar.io/wp-content/uploads/trash-icon.svg"/></button>
+                    </div>
+                })}
+                <button class="button property" onclick={ctx.link().callback(move |_| Msg::AddWhereClause(index))}><span class="plus">{"+"}</span>{"Add condition"}</button>
+                <label>{"Order by"}</label>
+                {for query.order_by.iter().enumerate().map(|(order_index, order)| html! {
+                    <div class="query-line">
+                        {self.view_query_field(&paths, &order.field, ctx.link().callback(move |e: Event| Msg::UpdateOrderByField(index, order_index, e.target_dyn_into::<HtmlSelectElement>().unwrap().value())))}
+                        <select onchange={ctx.link().callback(move |e: Event| Msg::UpdateOrderByDirection(index, order_index, e.target_dyn_into::<HtmlSelectElement>().unwrap().value() == "asc"))}>
+                            <option value="asc" selected={order.ascending}>{"asc"}</option>
+                            <option value="desc" selected={!order.ascending}>{"desc"}</option>
+                        </select>
+                        <button class="button remove" onclick={ctx.link().callback(move |_| Msg::RemoveOrderBy(index, order_index))}><img src="https://media.hellar.io/wp-content/uploads/trash-icon.svg"/></button>
+                    </div>
+                })}
+                <button class="button property" onclick={ctx.link().callback(move |_| Msg::AddOrderBy(index))}><span class="plus">{"+"}</span>{"Add ordering"}</button>
+                <label>{"Start at"}</label>
+                <input type="text" placeholder="Document id (optional)" value={query.start_at.clone()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateQueryStartAt(index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                {result}
+            </div>
+        }
+    }
+
+    /// The indices each document type needs to serve all of its queries
+    fn view_index_suggestions(&self, ctx: &yew::Context<Self>) -> Html {
+        let queried = self.document_types.iter()
+            .filter(|document_type| self.queries.iter().any(|query| query.document_type == document_type.name));
+        let unknown = query::unknown_document_types(&self.document_types, &self.queries);
+        html! {
+            <>
+            {for unknown.into_iter().map(|warning| html! {<p class="error-text">{warning}</p>})}
+            {for queried.map(|document_type| {
+                let suggestion = query::suggest_indices(document_type, &self.queries);
+                let name = document_type.name.clone();
+                html! {
+                    <div class="query-suggestion">
+                        <h4>{format!("Indices for \"{}\"", document_type.name)}</h4>
+                        {if suggestion.indices.is_empty() && suggestion.warnings.is_empty() {
+                            html! {<p class="passed-text">{"The existing indices serve all queries ✓"}</p>}
+                        } else { html! {} }}
+                        <ul>
+                            {for suggestion.indices.iter().map(|index| {
+                                let fields: Vec<&str> = index.properties.iter().map(|property| property.name.as_str()).collect();
+                                html! {<li>{format!("{}: {}", index.name, fields.join(", "))}</li>}
+                            })}
+                        </ul>
+                        {for suggestion.warnings.iter().map(|warning| html! {<p class="error-text">{warning.clone()}</p>})}
+                        if !suggestion.indices.is_empty() {
+                            <button class="button-clear wide" onclick={ctx.link().callback(move |_| Msg::AddSuggestedIndices(name.clone()))}>
+                                {format!("Add {} {}", suggestion.indices.len(), if suggestion.indices.len() == 1 { "index" } else { "indices" })}
+                            </button>
+                        }
+                    </div>
+                }
+            })}
+            </>
+        }
+    }
+
+    fn view_queries(&self, ctx: &yew::Context<Self>) -> Html {
+        html! {
+            <details class="query-section">
+                <summary>{"Query planner"}</summary>
+                <p>{"Describe the queries your app will run to check which index serves each of them."}</p>
+                {for (0..self.queries.len()).map(|index| self.view_query(ctx, index))}
+                <button class="button-clear wide" disabled={self.document_types.is_empty()} onclick={ctx.link().callback(|_| Msg::AddQuery)}>{"Add query"}</button>
+                <h4>{"Queries as text"}</h4>
+                <textarea class="textarea-no-whitespace" placeholder="post where $ownerId == and score > orderBy score desc" value={self.query_text.clone()} oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateQueryText(e.target_dyn_into::<web_sys::HtmlTextAreaElement>().unwrap().value()))}></textarea>
+                <button class="button-clear wide" disabled={self.query_text.trim().is_empty()} onclick={ctx.link().callback(|_| Msg::ImportQueries)}>{"Add queries"}</button>
+                {if let Some(error) = &self.query_text_error {
+                    html! {<p class="error-text">{error.clone()}</p>}
+                } else { html! {} }}
+                {self.view_index_suggestions(ctx)}
+            </details>
+        }
+    }
+
+    fn view_diagram(&self) -> Html {
+        html! {
+            <details class="diagram-section">
+                <summary>{"Diagram"}</summary>
+                <p>{"References are inferred from 32-byte identifier properties named after a document type, e.g. listingId."}</p>
+                <div class="diagram-container">
+                    {diagram::svg(&self.document_types)}
+                </div>
+            </details>
+        }
+    }
+
+    fn view_code(&self, ctx: &yew::Context<Self>) -> Html {
+        let selected = self.code_format;
+        html! {
+            <div class="code-section">
+                <h3>{"Generated code"}</h3>
+                <select onchange={ctx.link().callback(|e: Event| {
+                    let index = e.target_dyn_into::<HtmlSelectElement>().unwrap().selected_index().max(0) as usize;
+                    Msg::SetCodeFormat(CodeFormat::ALL[index.min(CodeFormat::ALL.len() - 1)])
+                })}>
+                    {for CodeFormat::ALL.iter().map(|format| html! {
+                        <option selected={*format == selected}>{format.label()}</option>
+                    })}
+                </select>
+                <pre>
+                    <textarea class="textarea-whitespace code" readonly=true value={self.generated_code()}></textarea>
+                </pre>
+                <div class="button-block">
+                    <button class="button-clear wide" onclick={ctx.link().callback(|_| Msg::CopyCode)}>{"Copy"}</button>
+                    <button class="button-clear wide" onclick={ctx.link().callback(|_| Msg::DownloadCode)}>{"Download"}</button>
+                </div>
+                {if let Some(status) = &self.code_status {
+                    html! {<p>{status.clone()}</p>}
+                } else { html! {} }}
+            </div>
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            document_types: self.document_types.clone(),
+            settings: self.settings.clone(),
+            json_object: self.json_object.clone(),
+            error_messages: self.error_messages.clone(),
+            schema: self.schema.clone(),
+        }
+    }
+
+    fn restore_snapshot(&mut self, snapshot: Snapshot) {
+        self.document_types = snapshot.document_types;
+        self.settings = snapshot.settings;
+        self.json_object = snapshot.json_object;
+        self.error_messages = snapshot.error_messages;
+        self.schema = snapshot.schema;
+    }
+
+    /// Pushes an undo step before the message is applied, if it changes the contract
+    fn record_undo(&mut self, msg: &Msg) {
+        match msg.undo_kind() {
+            UndoKind::Ignore => return,
+            UndoKind::Step => self.last_edit = None,
+            UndoKind::Typing(key) => {
+                if self.last_edit.as_ref() == Some(&key) {
+                    return;
+                }
+                self.last_edit = Some(key);
+            }
+        }
+        self.undo_stack.push(self.snapshot());
+        if self.undo_stack.len() > UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    fn view_history(&self, ctx: &yew::Context<Self>) -> Html {
+        if self.history.is_empty() {
+            return html! {};
+        }
+        html! {
+            <div class="prompt-history">
+                <h3>{"Prompt history:"}</h3>
+                {for self.history.iter().enumerate().map(|(i, entry)| {
+                    let class = if self.history_head == Some(i) { "history-entry current" } else { "history-entry" };
+                    html! {
+                        <div class={class} onclick={ctx.link().callback(move |_| Msg::PreviewHistoryEntry(i))}>
+                            <span class="history-version">{format!("v{}", i+1)}</span>
+                            {entry.prompt.clone()}
+                            {match entry.parent {
+                                Some(parent) if parent + 1 != i => html! {<span class="history-branch">{format!("from v{}", parent+1)}</span>},
+                                _ => html! {},
+                            }}
+                            {if !entry.error_messages.is_empty() {
+                                html! {<span class="history-errors">{format!("{} error(s)", entry.error_messages.len())}</span>}
+                            } else { html! {} }}
+                        </div>
+                    }
+                })}
+                {self.view_history_preview(ctx)}
+            </div>
+        }
+    }
+
+    fn view_history_preview(&self, ctx: &yew::Context<Self>) -> Html {
+        let index = match self.history_preview {
+            Some(index) => index,
+            None => return html! {},
+        };
+        let entry = &self.history[index];
+        let pretty = serde_json::from_str::<Value>(&entry.schema)
+            .and_then(|value| serde_json::to_string_pretty(&value))
+            .unwrap_or_else(|_| entry.schema.clone());
+        html! {
+            <div class="history-preview">
+                <h3>{format!("v{}: \"{}\"", index+1, entry.prompt)}</h3>
+                {if entry.error_messages.is_empty() {
+                    html! {<p class="passed-text">{"DPP validation passing ✓"}</p>}
+                } else {
+                    html! {
+                        <ul class="error-text">
+                            { for entry.error_messages.iter().map(|i| html! { <li>{i.clone()}</li> }) }
+                        </ul>
+                    }
+                }}
+                <pre>
+                    <textarea class="textarea-whitespace" readonly=true value={pretty}></textarea>
+                </pre>
+                <div class="button-block">
+                    <button class="button-clear" onclick={ctx.link().callback(|_| Msg::CloseHistoryPreview)}><span class="clear">{"X"}</span>{"Close"}</button>
+                    <button class="button-import" onclick={ctx.link().callback(move |_| Msg::RestoreHistoryEntry(index))}>{"Restore this version"}</button>
+                </div>
+            </div>
+        }
+    }
+
     fn view_document_types(&self, ctx: &yew::Context<Self>) -> Html {
         html! {
             <div>
@@ -417,6 +2013,47 @@ impl Model {
         }
     }
 
+    fn view_contract_settings(&self, ctx: &yew::Context<Self>) -> Html {
+        let checkbox = |label: &str, checked: bool, msg: fn(bool) -> Msg| html! {
+            <label class="container-checkbox second-checkbox">{format!("{}   ", label)}
+                <input type="checkbox" checked={checked} onchange={ctx.link().callback(move |e: Event| msg(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().checked()))} />
+                <span class="checkmark"></span>
+            </label>
+        };
+        html! {
+            <>
+                <div class="input-container">
+                    <div class="doc-section">
+                        <div class="doc-block">
+                            <h2>{"Contract settings"}</h2>
+                        </div>
+                        <label>{"Description"}</label>
+                        <input type="text"
+                            value={self.settings.description.clone()}
+                            oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateContractDescription(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))}
+                        />
+                        <label>{"Keywords"}</label>
+                        <input type="text"
+                            placeholder="Comma-separated"
+                            value={self.settings.keywords.clone()}
+                            oninput={ctx.link().callback(|e: InputEvent| Msg::UpdateContractKeywords(e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))}
+                        />
+                    </div>
+                    <div class="forms-line-checkboxes">
+                        {checkbox("Read-only", self.settings.readonly, Msg::UpdateContractReadonly)}
+                        {checkbox("Keep history", self.settings.keeps_history, Msg::UpdateContractKeepsHistory)}
+                        {checkbox("Can be deleted", self.settings.can_be_deleted, Msg::UpdateContractCanBeDeleted)}
+                    </div>
+                    <div class="forms-line-checkboxes">
+                        {checkbox("Documents mutable by default", self.settings.documents_mutable_contract_default, Msg::UpdateDocumentsMutableContractDefault)}
+                        {checkbox("Documents keep history by default", self.settings.documents_keep_history_contract_default, Msg::UpdateDocumentsKeepHistoryContractDefault)}
+                    </div>
+                </div>
+                <br/>
+            </>
+        }
+    }
+
     fn view_document_type(&self, index: usize, ctx: &yew::Context<Self>) -> Html {
         html! {
             <>
@@ -456,6 +2093,12 @@ impl Model {
                             </label>
                         </div>
                     </div>
+                    <div>
+                        <h3>{"Document settings"}</h3>
+                        {self.view_document_flag(ctx, "Documents mutable", self.document_types[index].documents_mutable, move |value| Msg::UpdateDocumentsMutable(index, value))}
+                        {self.view_document_flag(ctx, "Can be deleted", self.document_types[index].can_be_deleted, move |value| Msg::UpdateCanBeDeleted(index, value))}
+                        {self.view_document_flag(ctx, "Keep history", self.document_types[index].documents_keep_history, move |value| Msg::UpdateDocumentsKeepHistory(index, value))}
+                    </div>
                     <div>
                         <h3>{"Indices"}</h3>
                         {for (0..self.document_types[index].indices.len()).map(|i| self.view_index(index, i, ctx))}
@@ -479,6 +2122,27 @@ impl Model {
         }
     }
 
+    /// A yes/no select for a document type setting. Unset falls back to the contract's default.
+    fn view_document_flag(&self, ctx: &yew::Context<Self>, label: &str, value: Option<bool>, msg: impl Fn(Option<bool>) -> Msg + 'static) -> Html {
+        html! {
+            <div class="forms-line">
+                <label>{format!("{} ", label)}</label>
+                <select onchange={ctx.link().callback(move |e: Event| {
+                    let selected = e.target_dyn_into::<HtmlSelectElement>().unwrap().value();
+                    msg(match selected.as_str() {
+                        "Yes" => Some(true),
+                        "No" => Some(false),
+                        _ => None,
+                    })
+                })}>
+                    <option value="Default" selected={value.is_none()}>{"Contract default"}</option>
+                    <option value="Yes" selected={value == Some(true)}>{"Yes"}</option>
+                    <option value="No" selected={value == Some(false)}>{"No"}</option>
+                </select>
+            </div>
+        }
+    }
+
     fn view_property(&self, doc_index: usize, prop_index: usize, ctx: &yew::Context<Self>) -> Html {
         let data_type_options = vec!["String", "Integer", "Array", "Object", "Number", "Boolean"];
         let selected_data_type = match self.document_types[doc_index].properties[prop_index].data_type {
@@ -503,7 +2167,7 @@ impl Model {
                 <div class="forms-line-names">
                     <div class="form-headers">
                         <label>{"Name"}</label>
-                        <input type="text3" 
+                        <input type="text" class="text-field" 
                             //placeholder={format!("Property {} name", prop_index+1)} 
                             value={self.document_types[doc_index].properties[prop_index].name.clone()} 
                             oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdatePropertyName(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} 
@@ -540,11 +2204,11 @@ impl Model {
                     {additional_properties}
                     <div class="forms-line">
                         <label>{"Description "}</label>
-                        <input type="text3" value={self.document_types[doc_index].properties[prop_index].description.clone()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdatePropertyDescription(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                        <input type="text" class="text-field" value={self.document_types[doc_index].properties[prop_index].description.clone()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdatePropertyDescription(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
                     </div>                        
                     <div class="forms-line">
                         <label>{"Comment "}</label>
-                        <input type="text3" value={self.document_types[doc_index].properties[prop_index].comment.clone()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdatePropertyComment(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                        <input type="text" class="text-field" value={self.document_types[doc_index].properties[prop_index].comment.clone()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdatePropertyComment(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
                     </div>
                     <p></p>
                 </div>            
@@ -587,53 +2251,19 @@ impl Model {
                     </div>
                     <div class="forms-line">
                         <label>{"RE2 pattern "}</label>
-                        <input type="text3" value={property.pattern.clone().unwrap_or_default()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateStringPropertyPattern(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                        <input type="text" class="text-field" value={property.pattern.clone().unwrap_or_default()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateStringPropertyPattern(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
                     </div>
                     <div class="forms-line">
                         <label>{"Format "}</label>
-                        <input type="text3" value={property.format.clone().unwrap_or_default()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateStringPropertyFormat(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
-                    </div>
-                </>
-            },
-            "Integer" => html! {
-                <>
-                    <div class="forms-line number-block">
-                        <div class="forms-line min">
-                            <label>{"Minimum "}</label>
-                            <input type="number" value={property.minimum.map(|n| n.to_string()).unwrap_or_else(|| "".to_owned())} oninput={ctx.link().callback(move |e: InputEvent| {
-                                let value = e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value();
-                                let num_value = if value.is_empty() {
-                                    None
-                                } else {
-                                    Some(value.parse::<i32>().unwrap_or_default())
-                                };
-                                Msg::UpdateIntegerPropertyMinimum(doc_index, prop_index, num_value)
-                                })} 
-                            />
-                        </div>
-                        <div class="forms-line max">
-                            <label>{"Maximum "}</label>
-                            <input type="number" value={property.maximum.map(|n| n.to_string()).unwrap_or_else(|| "".to_owned())} oninput={ctx.link().callback(move |e: InputEvent| {
-                                let value = e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value();
-                                let num_value = if value.is_empty() {
-                                    None
-                                } else {
-                                    Some(value.parse::<i32>().unwrap_or_default())
-                                };
-                                Msg::UpdateIntegerPropertyMaximum(doc_index, prop_index, num_value)
-                                })} 
-                            />
-                        </div>
+                        <input type="text" class="text-field" value={property.format.clone().unwrap_or_default()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateStringPropertyFormat(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
                     </div>
                 </>
             },
+            "Integer" | "Number" => self.view_numeric_constraints(property, ctx, move |constraint, value| Msg::UpdateNumericConstraint(doc_index, prop_index, constraint, value)),
             "Array" => html! {
                 <>
+                    {self.view_array_items_type(property, doc_index, prop_index, None, ctx)}
                     <div class="forms-line number-block">
-                        /*<div class="forms-line">
-                            <label>{"Byte array: "}</label>
-                            <input type="checkbox" checked={property.byte_array.unwrap_or(false)} onchange={ctx.link().callback(move |e: Event| Msg::UpdateArrayPropertyByteArray(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().checked()))} />
-                        </div>*/
                         <div class="forms-line min">
                             <label>{"Min items "}</label>
                             <input type="number" value={property.min_items.map(|n| n.to_string()).unwrap_or_else(|| "".to_owned())} oninput={ctx.link().callback(move |e: InputEvent| {
@@ -661,10 +2291,13 @@ impl Model {
                         
                         </div>
                     </div>
-                    <div class="forms-line">
-                        <label>{"Content media type "}</label>
-                        <input type="text3" value={property.content_media_type.clone().unwrap_or_default()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateArrayPropertyCMT(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
-                    </div>
+                    if property.items.is_none() {
+                        <div class="forms-line">
+                            <label>{"Content media type "}</label>
+                            <input type="text" class="text-field" value={property.content_media_type.clone().unwrap_or_default()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateArrayPropertyCMT(doc_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                        </div>
+                    }
+                    {self.view_array_items(property, doc_index, prop_index, None, ctx)}
                 </>
             },
             "Object" => html! {
@@ -711,15 +2344,180 @@ impl Model {
                     </div>
                 </>
             },
-            "Number" => html! {
+            "Boolean" => html! {
                 <>
                 </>
             },
-            "Boolean" => html! {
+            _ => html! {},
+        }
+    }
+
+    /// Inputs for the numeric validation parameters of an integer or number property
+    fn view_numeric_constraints(&self, property: &Property, ctx: &yew::Context<Self>, msg: impl Fn(NumericConstraint, Option<Number>) -> Msg + Clone + 'static) -> Html {
+        let integer = property.data_type == DataType::Integer;
+        let (step, error) = if integer { ("1", "Enter a whole number.") } else { ("any", "Enter a number.") };
+        // Out-of-range integers are marked invalid by the browser too
+        let (min, max) = if integer { (Some(i64::MIN.to_string()), Some(i64::MAX.to_string())) } else { (None, None) };
+        let input = |constraint: NumericConstraint, class: &'static str| {
+            let msg = msg.clone();
+            let data_type = property.data_type.clone();
+            html! {
+                <div class={class}>
+                    <label>{constraint.label()}</label>
+                    // Unparsable text keeps the last valid value, and the browser marks the input invalid, showing the error below it
+                    <input type="number" step={step} min={min.clone()} max={max.clone()} value={constraint.get(property).map(|n| n.to_string()).unwrap_or_default()} oninput={ctx.link().batch_callback(move |e: InputEvent| {
+                        let input = e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap();
+                        let value = input.value();
+                        if value.trim().is_empty() {
+                            // Text the browser can't read as a number also has an empty value
+                            input.check_validity().then(|| msg(constraint, None))
+                        } else {
+                            parse_numeric(&data_type, &value).map(|number| msg(constraint, Some(number)))
+                        }
+                    })} />
+                    <p class="error-text numeric-error">{error}</p>
+                </div>
+            }
+        };
+        html! {
+            <>
+                <div class="forms-line number-block">
+                    {input(NumericConstraint::Minimum, "forms-line min")}
+                    {input(NumericConstraint::Maximum, "forms-line max")}
+                </div>
+                <div class="forms-line number-block">
+                    {input(NumericConstraint::ExclusiveMinimum, "forms-line min")}
+                    {input(NumericConstraint::ExclusiveMaximum, "forms-line max")}
+                </div>
+                {input(NumericConstraint::MultipleOf, "forms-line")}
+            </>
+        }
+    }
+
+    /// Whether an array holds bytes or items of a given type
+    fn view_array_items_type(&self, array: &Property, doc_index: usize, prop_index: usize, rec_prop_index: Option<usize>, ctx: &yew::Context<Self>) -> Html {
+        let item_type_options = vec!["String", "Integer", "Number", "Boolean", "Object"];
+        let selected = match array.items.as_deref() {
+            Some(items) => match items.data_type {
+                DataType::String => "String",
+                DataType::Integer => "Integer",
+                DataType::Number => "Number",
+                DataType::Boolean => "Boolean",
+                DataType::Object => "Object",
+                DataType::Array => "",
+            },
+            None if array.byte_array == Some(true) => "Bytes",
+            None => "",
+        };
+        html! {
+            <div class="forms-line">
+                <label>{"Items "}</label>
+                <select onchange={ctx.link().callback(move |e: Event| Msg::UpdateArrayItemsType(doc_index, prop_index, rec_prop_index, e.target_dyn_into::<HtmlSelectElement>().unwrap().value()))}>
+                    <option value="" selected={selected.is_empty()} disabled=true>{"Choose bytes or an item type"}</option>
+                    <option value="Bytes" selected={selected == "Bytes"}>{"Bytes (byte array)"}</option>
+                    {for item_type_options.iter().map(|option| html! {
+                        <option value={*option} selected={selected == *option}>{*option}</option>
+                    })}
+                </select>
+            </div>
+        }
+    }
+
+    /// The uniqueItems flag and the items schema of a typed array
+    fn view_array_items(&self, array: &Property, doc_index: usize, prop_index: usize, rec_prop_index: Option<usize>, ctx: &yew::Context<Self>) -> Html {
+        let items = match array.items.as_deref() {
+            Some(items) => items,
+            None => return html! {},
+        };
+        let length_input = |label: &'static str, value: Option<u32>, class: &'static str, msg: fn(usize, usize, Option<usize>, Option<u32>) -> Msg| html! {
+            <div class={class}>
+                <label>{label}</label>
+                <input type="number" value={value.map(|n| n.to_string()).unwrap_or_default()} oninput={ctx.link().callback(move |e: InputEvent| {
+                    let value = e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value();
+                    let num_value = if value.is_empty() {
+                        None
+                    } else {
+                        Some(value.parse::<u32>().unwrap_or_default())
+                    };
+                    msg(doc_index, prop_index, rec_prop_index, num_value)
+                })} />
+            </div>
+        };
+        let item_schema = match items.data_type {
+            DataType::String => html! {
                 <>
+                    <div class="forms-line number-block">
+                        {length_input("Item min length ", items.min_length, "forms-line min", Msg::UpdateItemsMinLength)}
+                        {length_input("Item max length ", items.max_length, "forms-line max", Msg::UpdateItemsMaxLength)}
+                    </div>
+                    <div class="forms-line">
+                        <label>{"Item RE2 pattern "}</label>
+                        <input type="text" class="text-field" value={items.pattern.clone().unwrap_or_default()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateItemsPattern(doc_index, prop_index, rec_prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                    </div>
+                    <div class="forms-line">
+                        <label>{"Item format "}</label>
+                        <input type="text" class="text-field" value={items.format.clone().unwrap_or_default()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateItemsFormat(doc_index, prop_index, rec_prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                    </div>
                 </>
             },
-            _ => html! {},
+            DataType::Integer | DataType::Number => self.view_numeric_constraints(items, ctx, move |constraint, value| Msg::UpdateItemsNumericConstraint(doc_index, prop_index, rec_prop_index, constraint, value)),
+            DataType::Object => {
+                let field_type_options = vec!["String", "Integer", "Number", "Boolean"];
+                let fields = items.properties.as_deref().cloned().unwrap_or_default();
+                html! {
+                    <>
+                        <h4 class="black">{"Item properties"}</h4>
+                        {for fields.iter().enumerate().map(|(field_index, field)| {
+                            let selected_field_type = match field.data_type {
+                                DataType::Integer => "Integer",
+                                DataType::Number => "Number",
+                                DataType::Boolean => "Boolean",
+                                _ => "String",
+                            };
+                            html! {
+                                <div class="forms-line-names">
+                                    <div class="form-headers">
+                                        <label>{"Name"}</label>
+                                        <input type="text" class="text-field" value={field.name.clone()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateItemsPropertyName(doc_index, prop_index, rec_prop_index, field_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                                    </div>
+                                    <div class="form-headers-type">
+                                        <label>{"Type"}</label>
+                                        <select onchange={ctx.link().callback(move |e: Event| Msg::UpdateItemsPropertyType(doc_index, prop_index, rec_prop_index, field_index, e.target_dyn_into::<HtmlSelectElement>().unwrap().value()))}>
+                                            {for field_type_options.iter().map(|option| html! {
+                                                <option value={*option} selected={selected_field_type == *option}>{*option}</option>
+                                            })}
+                                        </select>
+                                    </div>
+                                    <div class="form-headers checkbox-block">
+                                        <label>{"Required"}</label>
+                                        <label class="container-checkbox">
+                                            <input type="checkbox" checked={field.required} onchange={ctx.link().callback(move |e: Event| Msg::UpdateItemsPropertyRequired(doc_index, prop_index, rec_prop_index, field_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().checked()))} />
+                                            <span class="checkmark"></span>
+                                        </label>
+                                    </div>
+                                    <button class="button remove" onclick={ctx.link().callback(move |_| Msg::RemoveItemsProperty(doc_index, prop_index, rec_prop_index, field_index))}><img src="https://media.hellar.io/wp-content/uploads/trash-icon.svg"/></button>
+                                </div>
+                            }
+                        })}
+                        <div class="forms-line">
+                            <button class="button" onclick={ctx.link().callback(move |_| Msg::AddItemsProperty(doc_index, prop_index, rec_prop_index))}>{"Add item property"}</button>
+                        </div>
+                    </>
+                }
+            },
+            DataType::Boolean | DataType::Array => html! {},
+        };
+        html! {
+            <>
+                <div class="forms-line checkbox-block">
+                    <label>{"Unique items "}</label>
+                    <label class="container-checkbox">
+                        <input type="checkbox" checked={array.unique_items == Some(true)} onchange={ctx.link().callback(move |e: Event| Msg::UpdateArrayUniqueItems(doc_index, prop_index, rec_prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().checked()))} />
+                        <span class="checkmark"></span>
+                    </label>
+                </div>
+                {item_schema}
+            </>
         }
     }
 
@@ -759,7 +2557,7 @@ impl Model {
                                 }
                             </b>
                         </label>
-                        <input type="text3" 
+                        <input type="text" class="text-field" 
                             //placeholder={format!("Inner property {} name", recursive_prop_index+1)} 
                             value={match &self.document_types[doc_index].properties[prop_index].properties {
                                 Some(properties) => properties.get(recursive_prop_index).map(|property| property.name.clone()).unwrap_or_default(),
@@ -816,7 +2614,7 @@ impl Model {
                 <div class="forms-line">
                     {self.rec_render_additional_properties(&selected_data_type, doc_index, prop_index, recursive_prop_index, ctx)}
                     <label>{"Description "}</label>
-                    <input type="text3" 
+                    <input type="text" class="text-field" 
                         value={if let Some(properties) = &self.document_types.get(doc_index).and_then(|doc| doc.properties.get(prop_index).and_then(|prop| prop.properties.clone())) {
                             properties.get(recursive_prop_index).and_then(|prop| prop.description.clone()).unwrap_or_default()
                             } else { "".to_string() }} 
@@ -825,7 +2623,7 @@ impl Model {
                 </div>
                 <div class="forms-line">
                     <label>{"Comment "}</label>
-                    <input type="text3" 
+                    <input type="text" class="text-field" 
                         value={if let Some(properties) = &self.document_types.get(doc_index).and_then(|doc| doc.properties.get(prop_index).and_then(|prop| prop.properties.clone())) {
                             properties.get(recursive_prop_index).and_then(|prop| prop.comment.clone()).unwrap_or_default()
                             } else {"".to_string()}} 
@@ -878,60 +2676,30 @@ impl Model {
                         </div>
                         <div class="forms-line">
                             <label>{"RE2 pattern "}</label>
-                            <input type="text3" value={pattern} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateStringRecPropertyPattern(doc_index, prop_index, recursive_prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                            <input type="text" class="text-field" value={pattern} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateStringRecPropertyPattern(doc_index, prop_index, recursive_prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
                         </div>
                         <div class="forms-line">
                             <label>{"Format "}</label>
-                            <input type="text3" value={format} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateStringRecPropertyFormat(doc_index, prop_index, recursive_prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                            <input type="text" class="text-field" value={format} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateStringRecPropertyFormat(doc_index, prop_index, recursive_prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
                         </div>
                     </>
                 }
             },
-            "Integer" => {
-                let minimum = properties.and_then(|p| p.get(recursive_prop_index)).and_then(|p| p.minimum);
-                let maximum = properties.and_then(|p| p.get(recursive_prop_index)).and_then(|p| p.maximum);
-                html! {
-                    <>
-                        <div class="forms-line number-block">
-                            <div class="forms-line min">
-                                <label>{"Minimum "}</label>
-                                <input type="number" value={minimum.map(|n| n.to_string()).unwrap_or_else(|| "".to_owned())} oninput={ctx.link().callback(move |e: InputEvent| {
-                                    let value = e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value();
-                                    let num_value = if value.is_empty() {
-                                        None
-                                    } else {
-                                        Some(value.parse::<i32>().unwrap_or_default())
-                                    };
-                                    Msg::UpdateIntegerRecPropertyMinimum(doc_index, prop_index, recursive_prop_index, num_value)
-                                })} />
-                            </div>
-                            <div class="forms-line max">
-                                <label>{"Maximum "}</label>
-                                <input type="number" value={maximum.map(|n| n.to_string()).unwrap_or_else(|| "".to_owned())} oninput={ctx.link().callback(move |e: InputEvent| {
-                                    let value = e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value();
-                                    let num_value = if value.is_empty() {
-                                        None
-                                    } else {
-                                        Some(value.parse::<i32>().unwrap_or_default())
-                                    };
-                                    Msg::UpdateIntegerRecPropertyMaximum(doc_index, prop_index, recursive_prop_index, num_value)
-                                })} />
-                            </div>
-                        </div>
-                    </>
-                }
+            "Integer" | "Number" => match properties.and_then(|p| p.get(recursive_prop_index)) {
+                Some(property) => self.view_numeric_constraints(property, ctx, move |constraint, value| Msg::UpdateNumericRecConstraint(doc_index, prop_index, recursive_prop_index, constraint, value)),
+                None => html! {},
             },
             "Array" => {
-                //let byte_array = properties.and_then(|p| p.get(recursive_prop_index)).and_then(|p| p.byte_array);
+                let array = match properties.and_then(|p| p.get(recursive_prop_index)) {
+                    Some(array) => array,
+                    None => return html! {},
+                };
                 let max_items = properties.and_then(|p| p.get(recursive_prop_index)).and_then(|p| p.max_items);
                 let min_items = properties.and_then(|p| p.get(recursive_prop_index)).and_then(|p| p.min_items);
                 let content_media_type = properties.and_then(|p| p.get(recursive_prop_index)).and_then(|p| p.content_media_type.clone());
                 html! {
                     <>
-                        /*<div class="forms-line">
-                            <label>{"Byte array: "}</label>
-                            <input type="checkbox" checked={byte_array.unwrap_or(false)} onchange={ctx.link().callback(move |e: Event| Msg::UpdateArrayRecPropertyByteArray(doc_index, prop_index, recursive_prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().checked()))} />
-                        </div>*/
+                        {self.view_array_items_type(array, doc_index, prop_index, Some(recursive_prop_index), ctx)}
                         <div class="forms-line number-block">
                             <div class="forms-line min">
                                 <label>{"Min items "}</label>
@@ -958,10 +2726,13 @@ impl Model {
                                 })} />
                             </div>
                         </div>
-                        <div class="forms-line">
-                            <label>{"Content media type "}</label>
-                            <input type="text3" value={content_media_type} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateArrayRecPropertyCMT(doc_index, prop_index, recursive_prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
-                        </div>
+                        if array.items.is_none() {
+                            <div class="forms-line">
+                                <label>{"Content media type "}</label>
+                                <input type="text" class="text-field" value={content_media_type} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateArrayRecPropertyCMT(doc_index, prop_index, recursive_prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
+                            </div>
+                        }
+                        {self.view_array_items(array, doc_index, prop_index, Some(recursive_prop_index), ctx)}
                     </>
                 }
             },            
@@ -1001,10 +2772,6 @@ impl Model {
                     </>
                 }
             },
-            "Number" => html! {
-                <>
-                </>
-            },
             "Boolean" => html! {
                 <>
                 </>
@@ -1025,7 +2792,7 @@ impl Model {
                                 } else {{format!("Index {} name", index_index+1)}}
                             </b>
                         </label>
-                        <input type="text3"
+                        <input type="text" class="text-field"
                             //placeholder={format!("Index {} name", index_index+1)}
                             value={self.document_types[doc_index].indices[index_index].name.clone()} 
                             oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateIndexName(doc_index, index_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} 
@@ -1038,6 +2805,13 @@ impl Model {
                             <span class="checkmark"></span>
                         </label>
                     </div>
+                    <div class="form-headers checkbox-block" title="Index documents that have none of the indexed properties set">
+                        <label>{"Null searchable"}</label>
+                        <label class="container-checkbox">
+                            <input type="checkbox" checked={self.document_types[doc_index].indices[index_index].null_searchable.unwrap_or(true)} onchange={ctx.link().callback(move |e: Event| Msg::UpdateIndexNullSearchable(doc_index, index_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().checked()))} />
+                            <span class="checkmark"></span>
+                        </label>
+                    </div>
                     <div class="form-headers-remove">
                         <button class="button remove" onclick={ctx.link().callback(move |_| Msg::RemoveIndex(doc_index, index_index))}><img src="https://media.hellar.io/wp-content/uploads/trash-icon.svg"/></button>
                     </div>
@@ -1062,40 +2836,69 @@ impl Model {
     }    
 
     fn view_index_properties(&self, doc_index: usize, index_index: usize, prop_index: usize, ctx: &yew::Context<Self>) -> Html {
-        // let sorting_options = vec!["Ascending", "Descending"];
-        // let mut current_sort = sorting_options[0];
-        // if self.document_types[doc_index].indices[index_index].properties[prop_index].1.clone() == String::from("desc") {
-        //     current_sort = sorting_options[1];
-        // }
+        let document_type = &self.document_types[doc_index];
+        let index_property = &document_type.indices[index_index].properties[prop_index];
+        let paths = indexing::indexable_paths(document_type);
+        // Keep a name that isn't an indexable path selectable, so imported indices aren't silently changed
+        let unknown = !index_property.name.is_empty() && !paths.contains(&index_property.name);
+        // Platform only accepts ascending index properties. Descending ones are kept so imports round-trip, and DPP flags them.
+        let descending = index_property.order == "desc";
         html!(
             <div class="forms-line number-block index">
                 <div class="form-headers">
                     <p>
-                        if !self.document_types[doc_index].indices[index_index].properties[prop_index].0.is_empty() {
-                            {format!("\"{}\"", self.document_types[doc_index].indices[index_index].properties[prop_index].0)}
+                        if !index_property.name.is_empty() {
+                            {format!("\"{}\"", index_property.name)}
                         } else {{format!("Index {} property {} name", index_index+1, prop_index+1)}}
                     </p>
-                    <input type="text3" value={self.document_types[doc_index].indices[index_index].properties[prop_index].0.clone()} oninput={ctx.link().callback(move |e: InputEvent| Msg::UpdateIndexProperty(doc_index, index_index, prop_index, e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap().value()))} />
-                </div>
-                /*<div class="forms-line max index">    
-                    <select value={current_sort} onchange={ctx.link().callback(move |e: Event| Msg::UpdateIndexSorting(doc_index, index_index, prop_index, match e.target_dyn_into::<HtmlSelectElement>().unwrap().value().as_str() {
-                        "Ascending" => String::from("asc"),
-                        "Descending" => String::from("desc"),
-                        _ => panic!("Invalid data type selected"),
-                        }))}
-                        >
-                        {for sorting_options.iter().map(|option| html! {
-                            <option value={String::from(*option)} selected={&String::from(*option)==current_sort}>{String::from(*option)}</option>
+                    <select onchange={ctx.link().callback(move |e: Event| Msg::UpdateIndexProperty(doc_index, index_index, prop_index, e.target_dyn_into::<HtmlSelectElement>().unwrap().value()))}>
+                        <option value="" selected={index_property.name.is_empty()} disabled=true>{"Select a property"}</option>
+                        if unknown {
+                            <option value={index_property.name.clone()} selected=true>{format!("{} (not a property)", index_property.name)}</option>
+                        }
+                        {for paths.iter().map(|path| html! {
+                            <option value={path.clone()} selected={*path == index_property.name}>{path.clone()}</option>
                         })}
                     </select>
-                </div>*/  
-            </div>    
+                    {if let Some(problem) = indexing::index_property_problem(document_type, &index_property.name) {
+                        html! {<p class="error-text">{problem}</p>}
+                    } else { html! {} }}
+                </div>
+                <div class="forms-line max index">
+                    <select onchange={ctx.link().callback(move |e: Event| Msg::UpdateIndexSorting(doc_index, index_index, prop_index, e.target_dyn_into::<HtmlSelectElement>().unwrap().value()))}>
+                        <option value="asc" selected={!descending}>{"Ascending"}</option>
+                        <option value="desc" selected={descending} disabled={!descending} title="Platform only accepts ascending index properties">{"Descending"}</option>
+                    </select>
+                </div>
+            </div>
         )
     }
 
+    /// The array property at `prop_index`, or at `rec_prop_index` among its inner properties
+    fn array_property_mut(&mut self, doc_index: usize, prop_index: usize, rec_prop_index: Option<usize>) -> Option<&mut Property> {
+        let property = self.document_types.get_mut(doc_index)?.properties.get_mut(prop_index)?;
+        match rec_prop_index {
+            Some(rec_prop_index) => property.properties.as_mut()?.get_mut(rec_prop_index),
+            None => Some(property),
+        }
+    }
+
+    /// The items schema of the array property addressed as in `array_property_mut`
+    fn array_items_mut(&mut self, doc_index: usize, prop_index: usize, rec_prop_index: Option<usize>) -> Option<&mut Property> {
+        self.array_property_mut(doc_index, prop_index, rec_prop_index)?.items.as_deref_mut()
+    }
+
     fn generate_json_object(&mut self) -> Vec<String> {
+        let json_arr = Self::document_types_json(&mut self.document_types);
+        let s = json_arr.join(",");
+        self.schema = format!("{{{}}}", s);
+        json_arr
+    }
+
+    /// The `"name":{...}` JSON of each document type, updating their required lists on the way
+    fn document_types_json(document_types: &mut [DocumentType]) -> Vec<String> {
         let mut json_arr = Vec::new();
-        for doc_type in &mut self.document_types {
+        for doc_type in document_types.iter_mut() {
             let mut props_map = Map::new();
             for prop in &mut doc_type.properties {
                 let mut prop_obj = Map::new();
@@ -1122,11 +2925,10 @@ impl Model {
                 if prop.format.as_ref().map(|c| c.len()).unwrap_or(0) > 0 {
                     prop_obj.insert("format".to_owned(), json!(prop.format));
                 }
-                if prop.minimum.is_some() {
-                    prop_obj.insert("minimum".to_owned(), json!(prop.minimum));
-                }
-                if prop.maximum.is_some() {
-                    prop_obj.insert("maximum".to_owned(), json!(prop.maximum));
+                for constraint in NumericConstraint::ALL {
+                    if let Some(value) = constraint.get(prop) {
+                        prop_obj.insert(constraint.key().to_owned(), json!(value));
+                    }
                 }
                 if let Some(byte_array) = prop.byte_array {
                     prop_obj.insert("byteArray".to_owned(), json!(byte_array));
@@ -1140,6 +2942,12 @@ impl Model {
                 if prop.content_media_type.is_some() {
                     prop_obj.insert("contentMediaType".to_owned(), json!(prop.content_media_type));
                 }
+                if let Some(items) = prop.items.as_mut() {
+                    prop_obj.insert("items".to_owned(), json!(Self::generate_items(items)));
+                }
+                if let Some(unique_items) = prop.unique_items {
+                    prop_obj.insert("uniqueItems".to_owned(), json!(unique_items));
+                }
                 if prop.data_type == DataType::Object {
                     let rec_props_map = Self::generate_nested_properties(prop);
                     prop_obj.insert("properties".to_owned(), json!(rec_props_map));
@@ -1172,28 +2980,21 @@ impl Model {
             }
             let mut indices_arr = Vec::new();
             for index in &doc_type.indices {
+                let mut index_obj = json!({
+                    "name": index.name,
+                    "properties": index.properties.iter().map(|index_property| {
+                        let mut inner_obj = Map::new();
+                        inner_obj.insert(index_property.name.clone(), json!(index_property.order));
+                        json!(inner_obj)
+                    }).collect::<Vec<_>>(),
+                });
                 if index.unique {
-                    let index_obj = json!({
-                        "name": index.name,
-                        "properties": index.properties.iter().map(|inner_tuple| {
-                            let mut inner_obj = Map::new();
-                            inner_obj.insert(inner_tuple.0.clone(), json!(inner_tuple.1));
-                            json!(inner_obj)
-                        }).collect::<Vec<_>>(),
-                        "unique": index.unique,
-                    });
-                    indices_arr.push(index_obj);
-                } else {
-                    let index_obj = json!({
-                        "name": index.name,
-                        "properties": index.properties.iter().map(|inner_tuple| {
-                            let mut inner_obj = Map::new();
-                            inner_obj.insert(inner_tuple.0.clone(), json!(inner_tuple.1));
-                            json!(inner_obj)
-                        }).collect::<Vec<_>>(),
-                    });
-                    indices_arr.push(index_obj);
+                    index_obj["unique"] = json!(true);
+                }
+                if let Some(null_searchable) = index.null_searchable {
+                    index_obj["nullSearchable"] = json!(null_searchable);
                 }
+                indices_arr.push(index_obj);
             }
             let mut doc_obj = Map::new();
             doc_obj.insert("type".to_owned(), json!("object"));
@@ -1217,6 +3018,15 @@ impl Model {
                 doc_obj.insert("required".to_owned(), json!(doc_type.required));
             }
             doc_obj.insert("additionalProperties".to_owned(), json!(false));
+            if let Some(documents_mutable) = doc_type.documents_mutable {
+                doc_obj.insert("documentsMutable".to_owned(), json!(documents_mutable));
+            }
+            if let Some(can_be_deleted) = doc_type.can_be_deleted {
+                doc_obj.insert("canBeDeleted".to_owned(), json!(can_be_deleted));
+            }
+            if let Some(documents_keep_history) = doc_type.documents_keep_history {
+                doc_obj.insert("documentsKeepHistory".to_owned(), json!(documents_keep_history));
+            }
             if doc_type.comment.len() > 0 {
                 doc_obj.insert("$comment".to_owned(), json!(doc_type.comment));
             }
@@ -1226,8 +3036,6 @@ impl Model {
             let formatted_doc_obj = &final_doc_obj.to_string()[1..final_doc_obj.to_string().len()-1];
             json_arr.push(formatted_doc_obj.to_string());
         }
-        let s = json_arr.join(",");
-        self.schema = format!("{{{}}}", s);
         json_arr
     }    
 
@@ -1259,11 +3067,10 @@ impl Model {
                 if rec_prop.format.as_ref().map(|c| c.len()).unwrap_or(0) > 0 {
                     rec_prop_obj.insert("format".to_owned(), json!(rec_prop.format));
                 }
-                if rec_prop.minimum.is_some() {
-                    rec_prop_obj.insert("minimum".to_owned(), json!(rec_prop.minimum));
-                }
-                if rec_prop.maximum.is_some() {
-                    rec_prop_obj.insert("maximum".to_owned(), json!(rec_prop.maximum));
+                for constraint in NumericConstraint::ALL {
+                    if let Some(value) = constraint.get(rec_prop) {
+                        rec_prop_obj.insert(constraint.key().to_owned(), json!(value));
+                    }
                 }
                 if let Some(byte_array) = rec_prop.byte_array {
                     rec_prop_obj.insert("byteArray".to_owned(), json!(byte_array));
@@ -1277,6 +3084,12 @@ impl Model {
                 if rec_prop.content_media_type.as_ref().map(|c| c.len()).unwrap_or(0) > 0 {
                     rec_prop_obj.insert("contentMediaType".to_owned(), json!(rec_prop.content_media_type));
                 }
+                if let Some(items) = rec_prop.items.as_mut() {
+                    rec_prop_obj.insert("items".to_owned(), json!(Self::generate_items(items)));
+                }
+                if let Some(unique_items) = rec_prop.unique_items {
+                    rec_prop_obj.insert("uniqueItems".to_owned(), json!(unique_items));
+                }
                 if rec_prop.data_type == DataType::Object {
                     rec_prop_obj.insert("properties".to_owned(), json!({"some_property":{"type": "string"}}));
                 }
@@ -1308,18 +3121,141 @@ impl Model {
         rec_props_map
     }
 
+    /// The `items` schema of a typed array
+    fn generate_items(items: &mut Property) -> Map<String, Value> {
+        let mut items_obj = Map::new();
+        items_obj.insert("type".to_owned(), json!(match items.data_type {
+            DataType::String => "string",
+            DataType::Integer => "integer",
+            DataType::Array => "array",
+            DataType::Object => "object",
+            DataType::Number => "number",
+            DataType::Boolean => "boolean",
+        }));
+        if items.min_length.is_some() {
+            items_obj.insert("minLength".to_owned(), json!(items.min_length));
+        }
+        if items.max_length.is_some() {
+            items_obj.insert("maxLength".to_owned(), json!(items.max_length));
+        }
+        if items.pattern.as_ref().map(|c| c.len()).unwrap_or(0) > 0 {
+            items_obj.insert("pattern".to_owned(), json!(items.pattern));
+        }
+        if items.format.as_ref().map(|c| c.len()).unwrap_or(0) > 0 {
+            items_obj.insert("format".to_owned(), json!(items.format));
+        }
+        for constraint in NumericConstraint::ALL {
+            if let Some(value) = constraint.get(items) {
+                items_obj.insert(constraint.key().to_owned(), json!(value));
+            }
+        }
+        if items.data_type == DataType::Object {
+            let fields_map = Self::generate_nested_properties(items);
+            items_obj.insert("properties".to_owned(), json!(fields_map));
+            if items.rec_required.as_ref().map(|c| c.len()).unwrap_or_default() > 0 {
+                items_obj.insert("required".to_owned(), json!(items.rec_required));
+            }
+            items_obj.insert("additionalProperties".to_owned(), json!(false));
+        }
+        items_obj
+    }
+
+    /// Reads the `items` schema of a typed array. Arrays of arrays, and object or array fields
+    /// of object items, aren't supported by the form, so they're dropped with a warning.
+    fn parse_items(value: &Value, path: &str, warnings: &mut Vec<String>) -> Option<Property> {
+        let type_name = value.get("type").and_then(|v| v.as_str()).unwrap_or_default();
+        let data_type = match type_name {
+            "string" => DataType::String,
+            "integer" => DataType::Integer,
+            "number" => DataType::Number,
+            "boolean" => DataType::Boolean,
+            "object" => DataType::Object,
+            "array" => {
+                warnings.push(format!("{}: arrays of arrays aren't supported, so the items schema was dropped", path));
+                return None;
+            }
+            _ => {
+                warnings.push(format!("{}: items without a supported \"type\" were dropped", path));
+                return None;
+            }
+        };
+        let items_obj = value.as_object()?;
+        let mut items = Property { data_type, ..Default::default() };
+        items.min_length = items_obj.get("minLength").and_then(|v| v.as_u64()).map(|num| num as u32);
+        items.max_length = items_obj.get("maxLength").and_then(|v| v.as_u64()).map(|num| num as u32);
+        items.pattern = items_obj.get("pattern").and_then(|v| v.as_str()).map(|s| s.to_string());
+        items.format = items_obj.get("format").and_then(|v| v.as_str()).map(|s| s.to_string());
+        for constraint in NumericConstraint::ALL {
+            if let Some(Value::Number(value)) = items_obj.get(constraint.key()) {
+                constraint.set(&mut items, Some(value.clone()));
+            }
+        }
+        if items.data_type == DataType::Object {
+            let required = items_obj.get("required").and_then(|v| v.as_array()).cloned().unwrap_or_default();
+            let fields = items_obj.get("properties").and_then(|v| v.as_object()).map(|fields_map| {
+                fields_map.iter().filter_map(|(name, field_value)| {
+                    let field_path = format!("{}.{}", path, name);
+                    if let Some(nested @ ("object" | "array")) = field_value.get("type").and_then(|v| v.as_str()) {
+                        warnings.push(format!("{}: {} fields of array items aren't supported, so the field was dropped", field_path, nested));
+                        return None;
+                    }
+                    let mut field = Self::parse_items(field_value, &field_path, warnings)?;
+                    field.name = name.clone();
+                    field.required = required.iter().any(|v| v.as_str() == Some(name.as_str()));
+                    Some(field)
+                }).collect::<Vec<_>>()
+            });
+            items.properties = fields.map(Box::new);
+        }
+        Some(items)
+    }
+
     fn parse_imported_json(&mut self) {
 
-        // Parse the string into a HashMap
-        let parsed_json: HashMap<String, Value> = serde_json::from_str(&self.imported_json).unwrap_or_default();
+        // A full contract carries its settings next to the documents
+        if let Some(settings) = Self::parse_contract_settings(&self.imported_json) {
+            self.settings = settings;
+        }
+
+        // Parse the documents into a HashMap
+        let parsed_json: HashMap<String, Value> = Self::parse_contract_documents(&self.imported_json).unwrap_or_default();
 
         // Convert the HashMap into a Vec of Strings for json_object
         self.json_object = parsed_json.iter().map(|(k, v)| {
             format!("\"{}\":{}", k, v.to_string())
         }).collect();
 
-        // Empty self.document_types
-        self.document_types = Vec::new();
+        self.import_warnings.clear();
+        self.document_types = Self::parse_document_types(parsed_json, &mut self.import_warnings);
+    }
+
+    /// Parses the settings of a full contract, with a "documents" field. Bare documents objects have none.
+    fn parse_contract_settings(text: &str) -> Option<ContractSettings> {
+        let parsed: Value = serde_json::from_str(text).ok()?;
+        if parsed.get("documents").map_or(false, Value::is_object) {
+            Some(ContractSettings::from_contract_json(&parsed))
+        } else {
+            None
+        }
+    }
+
+    /// Parses contract JSON, either the documents object or a full contract with a "documents" field
+    fn parse_contract_documents(text: &str) -> Result<HashMap<String, Value>, String> {
+        let value: Value = serde_json::from_str(text).map_err(|e| format!("Invalid JSON: {}", e))?;
+        let documents = match value.get("documents") {
+            Some(documents) if documents.is_object() => documents.clone(),
+            _ => value,
+        };
+        match documents {
+            Value::Object(map) => Ok(map.into_iter().collect()),
+            _ => Err(String::from("Expected a JSON object of document types")),
+        }
+    }
+
+    /// Parses the document types of a contract's documents JSON. Schemas the form can't
+    /// represent are dropped and described in `warnings`.
+    fn parse_document_types(parsed_json: HashMap<String, Value>, warnings: &mut Vec<String>) -> Vec<DocumentType> {
+        let mut document_types = Vec::new();
 
         // Iterate over each key-value pair in the parsed JSON and push to document_types
         for (doc_type_name, doc_type_value) in parsed_json {
@@ -1387,11 +3323,10 @@ impl Model {
                                 if let Some(format) = prop_obj.get("format") {
                                     property.format = format.as_str().map(|s| s.to_string());
                                 }
-                                if let Some(minimum) = prop_obj.get("minimum") {
-                                    property.minimum = minimum.as_i64().map(|num| num as i32);
-                                }
-                                if let Some(maximum) = prop_obj.get("maximum") {
-                                    property.maximum = maximum.as_i64().map(|num| num as i32);
+                                for constraint in NumericConstraint::ALL {
+                                    if let Some(Value::Number(value)) = prop_obj.get(constraint.key()) {
+                                        constraint.set(&mut property, Some(value.clone()));
+                                    }
                                 }
                                 if let Some(min_items) = prop_obj.get("minItems") {
                                     property.min_items = min_items.as_u64().map(|num| num as u32);
@@ -1402,6 +3337,13 @@ impl Model {
                                 if let Some(content_media_type) = prop_obj.get("contentMediaType") {
                                     property.content_media_type = content_media_type.as_str().map(|s| s.to_string());
                                 }
+                                if let Some(items) = prop_obj.get("items") {
+                                    let path = format!("{}.{}.items", document_type.name, prop_name);
+                                    property.items = Self::parse_items(items, &path, warnings).map(Box::new);
+                                }
+                                if let Some(unique_items) = prop_obj.get("uniqueItems") {
+                                    property.unique_items = unique_items.as_bool();
+                                }
                                 if let Some(min_properties) = prop_obj.get("minProperties") {
                                     property.min_properties = min_properties.as_u64().map(|num| num as u32);
                                 }
@@ -1456,11 +3398,10 @@ impl Model {
                                                 if let Some(format) = nested_prop_obj.get("format") {
                                                     nested_property.format = format.as_str().map(|s| s.to_string());
                                                 }
-                                                if let Some(minimum) = nested_prop_obj.get("minimum") {
-                                                    nested_property.minimum = minimum.as_i64().map(|num| num as i32);
-                                                }
-                                                if let Some(maximum) = nested_prop_obj.get("maximum") {
-                                                    nested_property.maximum = maximum.as_i64().map(|num| num as i32);
+                                                for constraint in NumericConstraint::ALL {
+                                                    if let Some(Value::Number(value)) = nested_prop_obj.get(constraint.key()) {
+                                                        constraint.set(&mut nested_property, Some(value.clone()));
+                                                    }
                                                 }
                                                 if let Some(min_items) = nested_prop_obj.get("minItems") {
                                                     nested_property.min_items = min_items.as_u64().map(|num| num as u32);
@@ -1471,6 +3412,13 @@ impl Model {
                                                 if let Some(content_media_type) = nested_prop_obj.get("contentMediaType") {
                                                     nested_property.content_media_type = content_media_type.as_str().map(|s| s.to_string());
                                                 }
+                                                if let Some(items) = nested_prop_obj.get("items") {
+                                                    let path = format!("{}.{}.{}.items", document_type.name, prop_name, nested_prop_name);
+                                                    nested_property.items = Self::parse_items(items, &path, warnings).map(Box::new);
+                                                }
+                                                if let Some(unique_items) = nested_prop_obj.get("uniqueItems") {
+                                                    nested_property.unique_items = unique_items.as_bool();
+                                                }
                                                 if let Some(min_properties) = nested_prop_obj.get("minProperties") {
                                                     nested_property.min_properties = min_properties.as_u64().map(|num| num as u32);
                                                 }
@@ -1509,6 +3457,8 @@ impl Model {
                                     index.unique = unique.as_bool().unwrap();
                                 }
 
+                                index.null_searchable = index_obj.get("nullSearchable").and_then(|v| v.as_bool());
+
                                 // Iterate over index properties
                                 if let Some(properties) = index_obj.get("properties") {
                                     if let Some(properties_array) = properties.as_array() {
@@ -1520,8 +3470,8 @@ impl Model {
 
                                                 // Set index properties name and order
                                                 for (name, order) in prop_obj {
-                                                    index_properties.0 = name.to_string();
-                                                    index_properties.1 = order.as_str().unwrap().to_string();
+                                                    index_properties.name = name.to_string();
+                                                    index_properties.order = order.as_str().unwrap_or("asc").to_string();
                                                 }
 
                                                 // Add index properties to the Index
@@ -1542,38 +3492,165 @@ impl Model {
                         document_type.comment = comment.as_str().unwrap().to_string();
                     }
                 }
+
+                // Document type settings
+                document_type.documents_mutable = doc_type_obj.get("documentsMutable").and_then(|v| v.as_bool());
+                document_type.can_be_deleted = doc_type_obj.get("canBeDeleted").and_then(|v| v.as_bool());
+                document_type.documents_keep_history = doc_type_obj.get("documentsKeepHistory").and_then(|v| v.as_bool());
         
                 // Push to document_types
-                self.document_types.push(document_type);
+                document_types.push(document_type);
             }
         }
+        document_types
     }
 
-    fn validate(&mut self) -> Vec<String> {
+    fn data_contract_factory() -> dpp::data_contract::DataContractFactory {
+        let protocol_version_validator = dpp::version::ProtocolVersionValidator::default();
+        let data_contract_validator = dpp::data_contract::validation::data_contract_validator::DataContractValidator::new(Arc::new(protocol_version_validator));
+        dpp::data_contract::DataContractFactory::new(1, Arc::new(data_contract_validator))
+    }
+
+    /// Creates a DPP data contract from the current contract, owned by `owner_id`
+    fn create_data_contract(&self, owner_id: Identifier) -> Result<dpp::data_contract::CreatedDataContract, dpp::ProtocolError> {
         let s = &self.json_object.join(",");
         let new_s = format!("{{{}}}", s);
         let json_obj: serde_json::Value = serde_json::from_str(&new_s).unwrap();
+        Self::data_contract_factory().create(owner_id, json_obj.into(), Some(self.settings.config_json().into()), None)
+    }
+
+    /// What `cbor_contract` is built from, to tell when the serialized bytes are out of date
+    fn cbor_source(&self) -> (Vec<String>, Value, String, String) {
+        (self.json_object.clone(), self.settings.config_json(), self.owner_id.clone(), self.contract_id.clone())
+    }
+
+    /// The contract to serialize, with the ids entered for registration. Ids that aren't entered
+    /// are zero, so serializing the same contract twice gives the same bytes.
+    /// Returns the contract and a note on where its ids came from.
+    fn cbor_contract(&self) -> Result<(dpp::data_contract::DataContract, String), anyhow::Error> {
+        let owner_entered = !self.owner_id.trim().is_empty();
+        let contract_entered = !self.contract_id.trim().is_empty();
+        let owner_id = if owner_entered {
+            registration::parse_identifier(&self.owner_id, "owner identity id")?
+        } else {
+            Identifier::default()
+        };
+        let mut data_contract = self.create_data_contract(owner_id)
+            .map_err(|e| anyhow::anyhow!("{}", e))?
+            .data_contract;
+        data_contract.id = if contract_entered {
+            registration::parse_identifier(&self.contract_id, "contract id")?
+        } else {
+            Identifier::default()
+        };
+        let ids = match (owner_entered, contract_entered) {
+            (true, true) => "The contract and owner ids are the ones entered under Registration.",
+            (true, false) => "The owner id is the one entered under Registration. The contract id is the zero identifier.",
+            (false, true) => "The contract id is the one entered under Registration. The owner id is the zero identifier.",
+            (false, false) => "The contract and owner ids are zero identifiers. Enter them under Registration to serialize the registered contract.",
+        };
+        Ok((data_contract, String::from(ids)))
+    }
+
+    /// Builds the state transition of the current contract, owned by the entered identity
+    fn build_transition(&self) -> Result<registration::Transition, anyhow::Error> {
+        let owner_id = registration::parse_identifier(&self.owner_id, "owner identity id")?;
+        let created = self.create_data_contract(owner_id)
+            .map_err(|e| anyhow::anyhow!("Failed to create the data contract: {}", e))?;
+        let transition = match self.transition_kind {
+            registration::TransitionKind::Create => {
+                let create = Self::data_contract_factory().create_data_contract_create_transition(created)
+                    .map_err(|e| anyhow::anyhow!("Failed to build the transition: {}", e))?;
+                registration::ContractTransition::Create(create)
+            },
+            registration::TransitionKind::Update => {
+                let version: u32 = self.contract_version.trim().parse()
+                    .map_err(|_| anyhow::anyhow!("Enter the version of the registered contract."))?;
+                let mut data_contract = created.data_contract;
+                data_contract.id = registration::parse_identifier(&self.contract_id, "contract id")?;
+                data_contract.version = version.checked_add(1)
+                    .ok_or_else(|| anyhow::anyhow!("The registered contract version is already the highest possible."))?;
+                registration::ContractTransition::Update(dpp::data_contract::state_transition::data_contract_update_transition::DataContractUpdateTransition {
+                    protocol_version: dpp::version::LATEST_VERSION,
+                    data_contract,
+                    ..Default::default()
+                })
+            },
+        };
+        Ok(registration::Transition::new(transition, self.json_object.clone()))
+    }
+
+    /// Checks the form as an update of `baseline`, the previous version: the contract config and the
+    /// set of document types must stay the same, and DPP's schema and index compatibility checks must pass.
+    /// Returns the reasons Platform would reject the update with. Checks that need Platform's state aren't run.
+    fn update_consensus_errors(&self, mut baseline: Vec<DocumentType>) -> Result<Vec<String>, anyhow::Error> {
+        use dpp::data_contract::state_transition::data_contract_update_transition::validation::basic::{
+            validate_indices_are_backward_compatible, validate_schema_compatibility,
+        };
+        // Both versions get the same fixed owner, since only their schemas are compared
+        let owner_id = Identifier::default();
+        let previous_documents: Value = serde_json::from_str(&format!("{{{}}}", Self::document_types_json(&mut baseline).join(",")))?;
+        let previous_settings = self.baseline_settings.clone().unwrap_or_else(|| self.settings.clone());
+        let previous = Self::data_contract_factory()
+            .create(owner_id, previous_documents.into(), Some(previous_settings.config_json().into()), None)
+            .map_err(|e| anyhow::anyhow!("The previous version isn't a valid contract: {}", e))?
+            .data_contract;
+        let current = self.create_data_contract(owner_id)
+            .map_err(|e| anyhow::anyhow!("Failed to create the data contract: {}", e))?
+            .data_contract;
+
+        let mut errors = Vec::new();
+        if previous_settings.readonly {
+            errors.push(String::from("The previous version is read-only, so it can't be updated."));
+        }
+        let (previous_config, current_config) = (previous_settings.config_json(), self.settings.config_json());
+        for (key, value) in previous_config.as_object().into_iter().flatten() {
+            if current_config.get(key) != Some(value) {
+                errors.push(format!("The contract config can't be changed, but \"{}\" changed from {} to {}.", key, value, current_config[key]));
+            }
+        }
+        for name in previous.documents.keys().filter(|name| !current.documents.contains_key(*name)) {
+            errors.push(format!("Document type \"{}\" can't be removed.", name));
+        }
+        let consensus_errors = validate_indices_are_backward_compatible(previous.documents.iter(), current.documents.iter())?.errors;
+        errors.extend(consensus_errors.iter().map(|error| error.to_string()));
+        for (name, schema) in &previous.documents {
+            if let Some(new_schema) = current.documents.get(name) {
+                errors.extend(validate_schema_compatibility(schema, new_schema)?.errors.iter().map(|error| error.to_string()));
+            }
+        }
+        Ok(errors)
+    }
+
+    fn validate(&mut self) -> Vec<String> {
+        let contract_result = self.create_data_contract(Identifier::random());
     
-        let protocol_version_validator = dpp::version::ProtocolVersionValidator::default();
-        let data_contract_validator = dpp::data_contract::validation::data_contract_validator::DataContractValidator::new(Arc::new(protocol_version_validator));
-        let factory = dpp::data_contract::DataContractFactory::new(1, Arc::new(data_contract_validator));
-        let owner_id = Identifier::random();
-        let contract_result = factory
-            .create(owner_id, json_obj.clone().into(), None, None);
-    
+        let mut messages = self.settings.validate();
         match contract_result {
             Ok(contract) => {
                 let results = contract.data_contract.validate(&contract.data_contract.to_cleaned_object().unwrap()).unwrap_or_default();
                 let errors = results.errors;
-                self.extract_basic_error_messages(&errors)
+                messages.extend(self.extract_basic_error_messages(&errors));
             },
             Err(e) => {
                 self.error_messages_ai.push(format!("{}", e));
-                self.error_messages_ai.clone()
+                messages.extend(self.error_messages_ai.clone());
             }
         }
+        messages
     }
     
+    /// Messages of a consensus error returned by Platform, shown like validation errors
+    fn consensus_error_messages(&self, code: u32, message: &str, data: Option<&[u8]>) -> Vec<String> {
+        match data.map(ConsensusError::deserialize) {
+            Some(Ok(error)) => {
+                let messages = self.extract_basic_error_messages(std::slice::from_ref(&error));
+                if messages.is_empty() { vec![format!("{}", error)] } else { messages }
+            },
+            _ => vec![format!("Consensus error {}: {}", code, message)],
+        }
+    }
+
     /// Extracts the BasicError messages, since they are the only ones we are interested in
     fn extract_basic_error_messages(&self, errors: &[ConsensusError]) -> Vec<String> {
         let messages: Vec<String> = errors
@@ -1582,7 +3659,7 @@ impl Model {
                 if let ConsensusError::BasicError(inner) = error {
                     if let dpp::errors::consensus::basic::basic_error::BasicError::JsonSchemaError(json_error) = inner {
                         if json_error.error_summary().contains("\"items\" is a required property") {
-                            Some(String::from("JsonSchemaError: \"array\" properties must specify \"byteArray\": true. In the dynamic form, just change the property from an array to a string and back to an array again, and resubmit."))
+                            Some(String::from("JsonSchemaError: \"array\" properties must either be byte arrays or have an \"items\" schema. In the dynamic form, choose \"Bytes\" or an item type in the array's \"Items\" field, and resubmit."))
                         } else {
                             Some(format!("JsonSchemaError: {}, Path: {}", json_error.error_summary().to_string(), json_error.instance_path().to_string()))
                         }
@@ -1607,24 +3684,102 @@ impl Component for Model {
     type Message = Msg;
     type Properties = ();
 
-    fn create(_ctx: &Context<Self>) -> Self {
+    fn create(ctx: &Context<Self>) -> Self {
+        let link = ctx.link().clone();
+        let shortcut_listener = Closure::<dyn Fn(web_sys::KeyboardEvent)>::new(move |e: web_sys::KeyboardEvent| {
+            if (e.ctrl_key() || e.meta_key()) && e.key().eq_ignore_ascii_case("z") {
+                // Text fields keep the browser's own undo
+                let in_text_field = e.target().and_then(|target| target.dyn_into::<web_sys::HtmlElement>().ok()).map_or(false, |element| {
+                    element.is_content_editable()
+                        || element.dyn_ref::<web_sys::HtmlInputElement>().is_some()
+                        || element.dyn_ref::<web_sys::HtmlTextAreaElement>().is_some()
+                });
+                if in_text_field {
+                    return;
+                }
+                e.prevent_default();
+                link.send_message(if e.shift_key() { Msg::Redo } else { Msg::Undo });
+            }
+        });
+        let shortcut_listener = web_sys::window()
+            .and_then(|window| window.add_event_listener_with_callback("keydown", shortcut_listener.as_ref().unchecked_ref()).ok())
+            .map(|_| shortcut_listener);
+
         let default_document_type = DocumentType::default();
         //default_document_type.properties.push(Property::default());
-        Self {
+        let mut model = Self {
             document_types: vec![default_document_type],
+            settings: ContractSettings::default(),
             json_object: Vec::new(),
             imported_json: String::new(),
             error_messages: Vec::new(),
+            import_warnings: Vec::new(),
             prompt: String::new(),
             schema: String::new(),
             history: Vec::new(),
+            history_head: None,
+            history_preview: None,
             temp_prompt: None,
             loading: false,
+            generation: 0,
             error_messages_ai: Vec::new(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            last_edit: None,
+            shortcut_listener,
+            workspace: storage::load_workspace().unwrap_or_default(),
+            project_error: None,
+            file_error: None,
+            drag_over: false,
+            network_contract_id: String::new(),
+            network_loading: false,
+            network_error: None,
+            queries: Vec::new(),
+            query_text: String::new(),
+            query_text_error: None,
+            cbor: None,
+            cbor_input: String::new(),
+            cbor_input_encoding: serialization::TextEncoding::default(),
+            cbor_error: None,
+            code_format: CodeFormat::Rust,
+            code_status: None,
+            baseline: None,
+            baseline_settings: None,
+            baseline_input: String::new(),
+            baseline_error: None,
+            update_check: None,
+            diff_sources: [DiffSource::Baseline, DiffSource::Current],
+            diff_texts: [String::new(), String::new()],
+            owner_id: String::new(),
+            transition_kind: registration::TransitionKind::Create,
+            contract_id: String::new(),
+            contract_version: String::new(),
+            private_key: String::new(),
+            key_id: String::new(),
+            transition: None,
+            transition_error: None,
+            broadcasting: false,
+            broadcast_status: None,
+        };
+
+        // Restore the autosaved contents of the last open project
+        model.open_project(model.workspace.active);
+        model
+    }
+
+    fn destroy(&mut self, _ctx: &Context<Self>) {
+        if let (Some(window), Some(listener)) = (web_sys::window(), self.shortcut_listener.take()) {
+            let _ = window.remove_event_listener_with_callback("keydown", listener.as_ref().unchecked_ref());
         }
     }
 
     fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        if msg.request_generation().map_or(false, |generation| generation != self.generation) {
+            return false;
+        }
+        self.record_undo(&msg);
+        let save = msg.changes_saved_state();
+        let cbor_source = self.cbor.is_some().then(|| self.cbor_source());
         match msg {
             // General
             Msg::AddDocumentType => {
@@ -1640,6 +3795,7 @@ impl Component for Model {
                     name: String::new(),
                     unique: false,
                     properties: vec![IndexProperties::default()],
+                    null_searchable: None,
                 });
             }
             Msg::RemoveDocumentType(index) => {
@@ -1667,6 +3823,36 @@ impl Component for Model {
             Msg::UpdateName(index, name) => {
                 self.document_types[index].name = name;
             }
+            Msg::UpdateContractReadonly(value) => {
+                self.settings.readonly = value;
+            }
+            Msg::UpdateContractKeepsHistory(value) => {
+                self.settings.keeps_history = value;
+            }
+            Msg::UpdateContractCanBeDeleted(value) => {
+                self.settings.can_be_deleted = value;
+            }
+            Msg::UpdateDocumentsMutableContractDefault(value) => {
+                self.settings.documents_mutable_contract_default = value;
+            }
+            Msg::UpdateDocumentsKeepHistoryContractDefault(value) => {
+                self.settings.documents_keep_history_contract_default = value;
+            }
+            Msg::UpdateContractDescription(description) => {
+                self.settings.description = description;
+            }
+            Msg::UpdateContractKeywords(keywords) => {
+                self.settings.keywords = keywords;
+            }
+            Msg::UpdateDocumentsMutable(index, value) => {
+                self.document_types[index].documents_mutable = value;
+            }
+            Msg::UpdateCanBeDeleted(index, value) => {
+                self.document_types[index].can_be_deleted = value;
+            }
+            Msg::UpdateDocumentsKeepHistory(index, value) => {
+                self.document_types[index].documents_keep_history = value;
+            }
             Msg::UpdateComment(index, comment) => {
                 self.document_types[index].comment = comment;
             }
@@ -1677,11 +3863,15 @@ impl Component for Model {
                 self.document_types[doc_index].indices[index_index].name = name;
             }
             Msg::UpdateIndexProperty(doc_index, index_index, prop_index, prop) => {
-                self.document_types[doc_index].indices[index_index].properties[prop_index].0 = prop;
+                self.document_types[doc_index].indices[index_index].properties[prop_index].name = prop;
+            }
+            Msg::UpdateIndexSorting(doc_index, index_index, prop_index, sorting) => {
+                self.document_types[doc_index].indices[index_index].properties[prop_index].order = sorting;
+            }
+            Msg::UpdateIndexNullSearchable(doc_index, index_index, null_searchable) => {
+                // Searchable is Platform's default, so only the opt-out is written to the contract
+                self.document_types[doc_index].indices[index_index].null_searchable = if null_searchable { None } else { Some(false) };
             }
-            /* Msg::UpdateIndexSorting(doc_index, index_index, prop_index, sorting) => {
-                self.document_types[doc_index].indices[index_index].properties[prop_index].1 = sorting;
-            } */
             Msg::UpdatePropertyType(doc_index, prop_index, new_property) => {
                 let prop = &mut self.document_types[doc_index].properties[prop_index];
                 prop.data_type = new_property.data_type;
@@ -1691,9 +3881,14 @@ impl Component for Model {
                 prop.format = new_property.format;
                 prop.minimum = new_property.minimum;
                 prop.maximum = new_property.maximum;
+                prop.exclusive_minimum = new_property.exclusive_minimum;
+                prop.exclusive_maximum = new_property.exclusive_maximum;
+                prop.multiple_of = new_property.multiple_of;
                 prop.byte_array = new_property.byte_array;
                 prop.min_items = new_property.min_items;
                 prop.max_items = new_property.max_items;
+                prop.items = new_property.items;
+                prop.unique_items = new_property.unique_items;
                 prop.min_properties = new_property.min_properties;
                 prop.max_properties = new_property.max_properties;
             }
@@ -1730,11 +3925,8 @@ impl Component for Model {
             Msg::UpdateStringPropertyFormat(doc_index, prop_index, format) => {
                 self.document_types[doc_index].properties[prop_index].format = Some(format);
             }
-            Msg::UpdateIntegerPropertyMinimum(doc_index, prop_index, minimum) => {
-                self.document_types[doc_index].properties[prop_index].minimum = minimum;
-            }
-            Msg::UpdateIntegerPropertyMaximum(doc_index, prop_index, maximum) => {
-                self.document_types[doc_index].properties[prop_index].maximum = maximum;
+            Msg::UpdateNumericConstraint(doc_index, prop_index, constraint, value) => {
+                constraint.set(&mut self.document_types[doc_index].properties[prop_index], value);
             }
             /* Msg::UpdateArrayPropertyByteArray(doc_index, prop_index, byte_array) => {
                 self.document_types[doc_index].properties[prop_index].byte_array = Some(byte_array);
@@ -1773,9 +3965,14 @@ impl Component for Model {
                     max_length: None,
                     minimum: None,
                     maximum: None,
+                    exclusive_minimum: None,
+                    exclusive_maximum: None,
+                    multiple_of: None,
                     min_items: None,
                     max_items: None,
                     content_media_type: None,
+                    items: None,
+                    unique_items: None,
                     min_properties: None,
                     max_properties: None,
                 };
@@ -1810,7 +4007,12 @@ impl Component for Model {
                     _ => unreachable!(),
                 };
                 if let Some(property_vec) = self.document_types[doc_index].properties[prop_index].properties.as_mut() {
-                    property_vec[rec_prop_index].data_type = data_type;
+                    let property = &mut property_vec[rec_prop_index];
+                    // Arrays start out as byte arrays, as they do for top-level properties
+                    if data_type == DataType::Array && property.items.is_none() {
+                        property.byte_array = Some(true);
+                    }
+                    property.data_type = data_type;
                 }
             }
             Msg::UpdateRecPropertyRequired(doc_index, prop_index, rec_prop_index, required) => {
@@ -1848,14 +4050,9 @@ impl Component for Model {
                     property_vec[rec_prop_index].format = Some(format);
                 }
             }
-            Msg::UpdateIntegerRecPropertyMaximum(doc_index, prop_index, rec_prop_index, maximum) => {
+            Msg::UpdateNumericRecConstraint(doc_index, prop_index, rec_prop_index, constraint, value) => {
                 if let Some(property_vec) = self.document_types[doc_index].properties[prop_index].properties.as_mut() {
-                    property_vec[rec_prop_index].maximum = maximum;
-                }
-            }
-            Msg::UpdateIntegerRecPropertyMinimum(doc_index, prop_index, rec_prop_index, minimum) => {
-                if let Some(property_vec) = self.document_types[doc_index].properties[prop_index].properties.as_mut() {
-                    property_vec[rec_prop_index].minimum = minimum;
+                    constraint.set(&mut property_vec[rec_prop_index], value);
                 }
             }
             /* Msg::UpdateArrayRecPropertyByteArray(doc_index, prop_index, rec_prop_index, byte_array) => {
@@ -1878,6 +4075,78 @@ impl Component for Model {
                     property_vec[rec_prop_index].content_media_type = Some(cmt);
                 }
             }
+            Msg::UpdateArrayItemsType(doc_index, prop_index, rec_prop_index, item_type) => {
+                if let Some(array) = self.array_property_mut(doc_index, prop_index, rec_prop_index) {
+                    if item_type == "Bytes" {
+                        array.byte_array = Some(true);
+                        array.items = None;
+                        array.unique_items = None;
+                    } else {
+                        array.byte_array = None;
+                        array.content_media_type = None;
+                        array.items = Some(Box::new(default_additional_properties(&item_type)));
+                    }
+                }
+            }
+            Msg::UpdateArrayUniqueItems(doc_index, prop_index, rec_prop_index, unique_items) => {
+                if let Some(array) = self.array_property_mut(doc_index, prop_index, rec_prop_index) {
+                    array.unique_items = if unique_items { Some(true) } else { None };
+                }
+            }
+            Msg::UpdateItemsMinLength(doc_index, prop_index, rec_prop_index, min_length) => {
+                if let Some(items) = self.array_items_mut(doc_index, prop_index, rec_prop_index) {
+                    items.min_length = min_length;
+                }
+            }
+            Msg::UpdateItemsMaxLength(doc_index, prop_index, rec_prop_index, max_length) => {
+                if let Some(items) = self.array_items_mut(doc_index, prop_index, rec_prop_index) {
+                    items.max_length = max_length;
+                }
+            }
+            Msg::UpdateItemsPattern(doc_index, prop_index, rec_prop_index, pattern) => {
+                if let Some(items) = self.array_items_mut(doc_index, prop_index, rec_prop_index) {
+                    items.pattern = Some(pattern);
+                }
+            }
+            Msg::UpdateItemsFormat(doc_index, prop_index, rec_prop_index, format) => {
+                if let Some(items) = self.array_items_mut(doc_index, prop_index, rec_prop_index) {
+                    items.format = Some(format);
+                }
+            }
+            Msg::UpdateItemsNumericConstraint(doc_index, prop_index, rec_prop_index, constraint, value) => {
+                if let Some(items) = self.array_items_mut(doc_index, prop_index, rec_prop_index) {
+                    constraint.set(items, value);
+                }
+            }
+            Msg::AddItemsProperty(doc_index, prop_index, rec_prop_index) => {
+                if let Some(items) = self.array_items_mut(doc_index, prop_index, rec_prop_index) {
+                    items.properties.get_or_insert_with(Default::default).push(Property::default());
+                }
+            }
+            Msg::RemoveItemsProperty(doc_index, prop_index, rec_prop_index, field_index) => {
+                if let Some(fields) = self.array_items_mut(doc_index, prop_index, rec_prop_index).and_then(|items| items.properties.as_mut()) {
+                    fields.remove(field_index);
+                }
+            }
+            Msg::UpdateItemsPropertyName(doc_index, prop_index, rec_prop_index, field_index, name) => {
+                if let Some(fields) = self.array_items_mut(doc_index, prop_index, rec_prop_index).and_then(|items| items.properties.as_mut()) {
+                    fields[field_index].name = name;
+                }
+            }
+            Msg::UpdateItemsPropertyType(doc_index, prop_index, rec_prop_index, field_index, data_type) => {
+                if let Some(fields) = self.array_items_mut(doc_index, prop_index, rec_prop_index).and_then(|items| items.properties.as_mut()) {
+                    fields[field_index] = Property {
+                        name: fields[field_index].name.clone(),
+                        required: fields[field_index].required,
+                        ..default_additional_properties(&data_type)
+                    };
+                }
+            }
+            Msg::UpdateItemsPropertyRequired(doc_index, prop_index, rec_prop_index, field_index, required) => {
+                if let Some(fields) = self.array_items_mut(doc_index, prop_index, rec_prop_index).and_then(|items| items.properties.as_mut()) {
+                    fields[field_index].required = required;
+                }
+            }
             Msg::UpdateObjectRecPropertyMinProperties(doc_index, prop_index, rec_prop_index, min_props) => {
                 if let Some(property_vec) = self.document_types[doc_index].properties[prop_index].properties.as_mut() {
                     property_vec[rec_prop_index].min_properties = min_props;
@@ -1904,6 +4173,7 @@ impl Component for Model {
                 self.json_object = vec![];
                 self.imported_json = String::new();
                 self.error_messages = vec![];
+                self.import_warnings.clear();
             }
             
             // OpenAI
@@ -1929,7 +4199,8 @@ impl Component for Model {
         
                 self.loading = true;
 
-                let callback = ctx.link().callback(Msg::ReceiveSchema);
+                let generation = self.generation;
+                let callback = ctx.link().callback(move |result| Msg::ReceiveSchema(generation, result));
                 spawn_local(async move {
                     let result = call_openai(&prompt).await;
                     callback.emit(result);
@@ -1941,14 +4212,9 @@ impl Component for Model {
             Msg::ClearInput => {
                 self.prompt.clear();
             },
-            Msg::ReceiveSchema(result) => {
+            Msg::ReceiveSchema(_, result) => {
                 match result {
                     Ok(schema) => {
-                        // Get the saved prompt and add it to the history
-                        if let Some(temp_prompt) = self.temp_prompt.take() {
-                            self.history.push(temp_prompt);
-                        }
-            
                         self.schema = schema.clone();
                         self.imported_json = schema;
                         self.parse_imported_json();
@@ -1956,6 +4222,16 @@ impl Component for Model {
                         self.error_messages = Some(self.validate()).unwrap();
                         self.imported_json = String::new();
 
+                        // Get the saved prompt and add it to the history along with the resulting contract
+                        if let Some(temp_prompt) = self.temp_prompt.take() {
+                            self.history.push(HistoryEntry {
+                                prompt: temp_prompt,
+                                schema: self.full_contract_json().to_string(),
+                                error_messages: self.error_messages.clone(),
+                                parent: self.history_head,
+                            });
+                            self.history_head = Some(self.history.len() - 1);
+                        }
                     },
                     Err(err) => {
                         self.error_messages_ai = vec![err.to_string()];
@@ -1963,6 +4239,509 @@ impl Component for Model {
                 }
                 self.loading = false;
             },
+
+            // History
+            Msg::PreviewHistoryEntry(index) => {
+                self.history_preview = Some(index);
+            },
+            Msg::RestoreHistoryEntry(index) => {
+                if let Some(entry) = self.history.get(index).cloned() {
+                    self.imported_json = entry.schema;
+                    self.parse_imported_json();
+                    // The next prompt continues from the restored document types, as it would from the AI's own output
+                    self.schema = format!("{{{}}}", self.json_object.join(","));
+                    self.json_object = Some(self.generate_json_object()).unwrap();
+                    self.error_messages = Some(self.validate()).unwrap();
+                    self.imported_json = String::new();
+                    self.history_head = Some(index);
+                    self.history_preview = None;
+                }
+            },
+            Msg::CloseHistoryPreview => {
+                self.history_preview = None;
+            },
+
+            // Undo/redo
+            Msg::Undo => {
+                if let Some(snapshot) = self.undo_stack.pop() {
+                    self.redo_stack.push(self.snapshot());
+                    self.restore_snapshot(snapshot);
+                }
+                self.last_edit = None;
+            },
+            Msg::Redo => {
+                if let Some(snapshot) = self.redo_stack.pop() {
+                    self.undo_stack.push(self.snapshot());
+                    self.restore_snapshot(snapshot);
+                }
+                self.last_edit = None;
+            },
+
+            // Autosave
+            Msg::StartFresh => {
+                let confirmed = web_sys::window()
+                    .and_then(|window| window.confirm_with_message("Discard this project's contract and prompt history and start fresh?").ok())
+                    .unwrap_or(false);
+                if confirmed {
+                    let active = self.workspace.active;
+                    let name = self.workspace.projects[active].name.clone();
+                    self.workspace.projects[active] = storage::Project::new(name);
+                    self.open_project(active);
+                }
+            },
+
+            // Projects
+            Msg::SwitchProject(index) => {
+                if index < self.workspace.projects.len() && index != self.workspace.active {
+                    self.save();
+                    self.open_project(index);
+                }
+            },
+            Msg::NewProject => {
+                self.save();
+                let name = format!("Project {}", self.workspace.projects.len() + 1);
+                self.workspace.projects.push(storage::Project::new(name));
+                self.open_project(self.workspace.projects.len() - 1);
+            },
+            Msg::RenameProject(name) => {
+                self.workspace.projects[self.workspace.active].name = name;
+            },
+            Msg::DuplicateProject => {
+                self.save();
+                let mut project = self.workspace.projects[self.workspace.active].clone();
+                project.name = format!("{} (copy)", project.name);
+                self.workspace.projects.push(project);
+                self.open_project(self.workspace.projects.len() - 1);
+            },
+            Msg::DeleteProject => {
+                let active = self.workspace.active;
+                let confirmed = web_sys::window()
+                    .and_then(|window| window.confirm_with_message(&format!("Delete the project \"{}\"?", self.workspace.projects[active].name)).ok())
+                    .unwrap_or(false);
+                if confirmed {
+                    self.workspace.projects.remove(active);
+                    if self.workspace.projects.is_empty() {
+                        self.workspace.projects.push(storage::Project::new(String::from("Project 1")));
+                    }
+                    self.open_project(active.min(self.workspace.projects.len() - 1));
+                }
+            },
+            Msg::ExportProject => {
+                self.save();
+                let project = &self.workspace.projects[self.workspace.active];
+                let filename = format!("{}.project.json", files::file_stem(&project.name));
+                if let Err(err) = files::download(&filename, storage::export_project(project).as_bytes(), "application/json") {
+                    self.project_error = Some(err.to_string());
+                }
+            },
+            Msg::OpenProjectFile(file) => {
+                let callback = ctx.link().callback(Msg::ReceiveProjectFile);
+                spawn_local(async move {
+                    callback.emit(files::read_text(file).await);
+                });
+            },
+            Msg::ReceiveProjectFile(result) => {
+                match result.and_then(|text| storage::import_project(&text)) {
+                    Ok(project) => {
+                        self.save();
+                        self.workspace.projects.push(project);
+                        self.open_project(self.workspace.projects.len() - 1);
+                    },
+                    Err(err) => {
+                        self.project_error = Some(err.to_string());
+                    },
+                }
+            },
+
+            // Contract files
+            Msg::DownloadContract => {
+                let filename = format!("{}.json", files::file_stem(&self.workspace.projects[self.workspace.active].name));
+                self.file_error = files::download(&filename, self.contract_json_pretty().as_bytes(), "application/json").err().map(|e| e.to_string());
+            },
+            Msg::DownloadFullContract => {
+                let filename = format!("{}.contract.json", files::file_stem(&self.workspace.projects[self.workspace.active].name));
+                let json = serde_json::to_string_pretty(&self.full_contract_json()).unwrap_or_default();
+                self.file_error = files::download(&filename, json.as_bytes(), "application/json").err().map(|e| e.to_string());
+            },
+            Msg::OpenContractFile(file) => {
+                self.drag_over = false;
+                let callback = ctx.link().callback(Msg::ReceiveContractFile);
+                spawn_local(async move {
+                    callback.emit(files::read_text(file).await);
+                });
+            },
+            Msg::ReceiveContractFile(result) => {
+                match result {
+                    Ok(text) => {
+                        // Go through the same path as pasting into the text area and clicking "Import"
+                        self.file_error = None;
+                        ctx.link().send_message_batch(vec![Msg::UpdateImportedJson(text), Msg::Import]);
+                    },
+                    Err(err) => {
+                        self.file_error = Some(err.to_string());
+                    },
+                }
+            },
+            Msg::UpdateNetworkContractId(contract_id) => {
+                self.network_contract_id = contract_id;
+            },
+            Msg::LoadFromNetwork => {
+                match registration::parse_identifier(&self.network_contract_id, "contract id") {
+                    Ok(id) => {
+                        self.network_loading = true;
+                        self.network_error = None;
+                        let endpoint = self.workspace.dapi_endpoint.clone();
+                        let generation = self.generation;
+                        let callback = ctx.link().callback(move |result| Msg::ReceiveNetworkContract(generation, result));
+                        spawn_local(async move {
+                            callback.emit(dapi::get_data_contract(&endpoint, &id.to_buffer()).await);
+                        });
+                    },
+                    Err(err) => {
+                        self.network_error = Some(err.to_string());
+                    },
+                }
+            },
+            Msg::ReceiveNetworkContract(_, result) => {
+                self.network_loading = false;
+                let metadata = self.settings.metadata_json();
+                let loaded = result
+                    .map_err(|e| anyhow::anyhow!("Failed to load the contract: {}", e))
+                    .and_then(|bytes| serialization::contract_from_cbor(&bytes))
+                    .and_then(|contract| Ok((serialization::contract_json(&contract, &metadata)?, contract)));
+                match loaded {
+                    Ok((documents, contract)) => {
+                        self.baseline = Self::parse_contract_documents(&documents).ok().map(|documents| Self::parse_document_types(documents, &mut Vec::new()));
+                        self.baseline_settings = Self::parse_contract_settings(&documents);
+                        self.contract_id = contract.id.to_string(dpp::platform_value::string_encoding::Encoding::Base58);
+                        self.contract_version = contract.version.to_string();
+                        self.owner_id = contract.owner_id.to_string(dpp::platform_value::string_encoding::Encoding::Base58);
+                        self.transition_kind = registration::TransitionKind::Update;
+                        self.network_contract_id.clear();
+                        self.network_error = None;
+                        ctx.link().send_message_batch(vec![Msg::UpdateImportedJson(documents), Msg::Import]);
+                    },
+                    Err(err) => {
+                        self.network_error = Some(err.to_string());
+                    },
+                }
+            },
+            Msg::SetDragOver(drag_over) => {
+                self.drag_over = drag_over;
+            },
+
+            // Query planner
+            Msg::AddQuery => {
+                let document_type = self.document_types.first().map(|document_type| document_type.name.clone()).unwrap_or_default();
+                self.queries.push(query::Query { document_type, ..Default::default() });
+            },
+            Msg::RemoveQuery(index) => {
+                self.queries.remove(index);
+            },
+            Msg::SetQueryDocumentType(index, document_type) => {
+                // The fields of one document type don't apply to another
+                self.queries[index] = query::Query { document_type, ..Default::default() };
+            },
+            Msg::AddWhereClause(index) => {
+                self.queries[index].where_clauses.push(Default::default());
+            },
+            Msg::RemoveWhereClause(index, clause) => {
+                self.queries[index].where_clauses.remove(clause);
+            },
+            Msg::UpdateWhereField(index, clause, field) => {
+                self.queries[index].where_clauses[clause].field = field;
+            },
+            Msg::UpdateWhereOperator(index, clause, operator) => {
+                self.queries[index].where_clauses[clause].operator = operator;
+            },
+            Msg::AddOrderBy(index) => {
+                self.queries[index].order_by.push(Default::default());
+            },
+            Msg::RemoveOrderBy(index, order) => {
+                self.queries[index].order_by.remove(order);
+            },
+            Msg::UpdateOrderByField(index, order, field) => {
+                self.queries[index].order_by[order].field = field;
+            },
+            Msg::UpdateOrderByDirection(index, order, ascending) => {
+                self.queries[index].order_by[order].ascending = ascending;
+            },
+            Msg::UpdateQueryStartAt(index, start_at) => {
+                self.queries[index].start_at = start_at;
+            },
+            Msg::AddSuggestedIndex(index) => {
+                let query = &self.queries[index];
+                if let Some(document_type) = self.document_types.iter_mut().find(|document_type| document_type.name == query.document_type) {
+                    if let query::Plan::Missing(suggested) = query::plan(document_type, query) {
+                        document_type.indices.push(suggested);
+                    }
+                }
+            },
+            Msg::UpdateQueryText(text) => {
+                self.query_text = text;
+            },
+            Msg::ImportQueries => {
+                match query::parse_queries(&self.query_text) {
+                    Ok(queries) => {
+                        self.queries.extend(queries);
+                        self.query_text.clear();
+                        self.query_text_error = None;
+                    },
+                    Err(err) => {
+                        self.query_text_error = Some(err);
+                    },
+                }
+            },
+            Msg::AddSuggestedIndices(name) => {
+                if let Some(document_type) = self.document_types.iter_mut().find(|document_type| document_type.name == name) {
+                    let suggestion = query::suggest_indices(document_type, &self.queries);
+                    document_type.indices.extend(suggestion.indices);
+                }
+            },
+
+            // Binary serialization
+            Msg::SerializeCbor => {
+                let result = self.cbor_contract().and_then(|(contract, ids)| {
+                    let bytes = contract.to_cbor_buffer().map_err(|e| anyhow::anyhow!("{}", e))?;
+                    Ok((bytes, ids))
+                });
+                match result {
+                    Ok(cbor) => {
+                        self.cbor = Some(cbor);
+                        self.cbor_error = None;
+                    },
+                    Err(e) => {
+                        self.cbor = None;
+                        self.cbor_error = Some(format!("Failed to serialize the contract: {}", e));
+                    },
+                }
+            },
+            Msg::DownloadCbor => {
+                if let Some((bytes, _)) = &self.cbor {
+                    let filename = format!("{}.cbor", files::file_stem(&self.workspace.projects[self.workspace.active].name));
+                    self.cbor_error = files::download(&filename, bytes, "application/cbor").err().map(|e| e.to_string());
+                }
+            },
+            Msg::UpdateCborInput(input) => {
+                self.cbor_input = input;
+            },
+            Msg::SetCborInputEncoding(encoding) => {
+                self.cbor_input_encoding = encoding;
+            },
+            Msg::ImportCbor => {
+                let bytes = serialization::decode_text(&self.cbor_input, self.cbor_input_encoding);
+                ctx.link().send_message(Msg::ReceiveCborFile(bytes));
+            },
+            Msg::OpenCborFile(file) => {
+                let callback = ctx.link().callback(Msg::ReceiveCborFile);
+                spawn_local(async move {
+                    callback.emit(files::read_bytes(file).await);
+                });
+            },
+            Msg::ReceiveCborFile(result) => {
+                let metadata = self.settings.metadata_json();
+                match result.and_then(|bytes| serialization::contract_json_from_cbor(&bytes, &metadata)) {
+                    Ok(documents) => {
+                        self.cbor_error = None;
+                        self.cbor_input.clear();
+                        ctx.link().send_message_batch(vec![Msg::UpdateImportedJson(documents), Msg::Import]);
+                    },
+                    Err(err) => {
+                        self.cbor_error = Some(err.to_string());
+                    },
+                }
+            },
+
+            // Generated code
+            Msg::SetCodeFormat(format) => {
+                self.code_format = format;
+                self.code_status = None;
+            },
+            Msg::CopyCode => {
+                let code = self.generated_code();
+                let callback = ctx.link().callback(Msg::SetCodeStatus);
+                spawn_local(async move {
+                    let status = match files::copy_to_clipboard(&code).await {
+                        Ok(()) => String::from("Copied to clipboard ✓"),
+                        Err(err) => format!("Failed to copy: {}", err),
+                    };
+                    callback.emit(status);
+                });
+            },
+            Msg::DownloadCode => {
+                let filename = self.code_format.filename(&files::file_stem(&self.workspace.projects[self.workspace.active].name));
+                self.code_status = files::download(&filename, self.generated_code().as_bytes(), self.code_format.mime_type())
+                    .err()
+                    .map(|err| format!("Failed to download: {}", err));
+            },
+            Msg::SetCodeStatus(status) => {
+                self.code_status = Some(status);
+            },
+
+            // Update compatibility
+            Msg::UpdateBaselineInput(text) => {
+                self.baseline_input = text;
+            },
+            Msg::LoadBaseline => {
+                match Self::parse_contract_documents(&self.baseline_input) {
+                    Ok(documents) => {
+                        self.baseline = Some(Self::parse_document_types(documents, &mut Vec::new()));
+                        self.baseline_settings = Self::parse_contract_settings(&self.baseline_input);
+                        self.baseline_input.clear();
+                        self.baseline_error = None;
+                    },
+                    Err(err) => {
+                        self.baseline_error = Some(err);
+                    },
+                }
+            },
+            Msg::OpenBaselineFile(file) => {
+                let callback = ctx.link().callback(Msg::ReceiveBaselineFile);
+                spawn_local(async move {
+                    callback.emit(files::read_text(file).await);
+                });
+            },
+            Msg::ReceiveBaselineFile(result) => {
+                match result {
+                    Ok(text) => {
+                        self.baseline_input = text;
+                        ctx.link().send_message(Msg::LoadBaseline);
+                    },
+                    Err(err) => {
+                        self.baseline_error = Some(format!("Failed to read file: {}", err));
+                    },
+                }
+            },
+            Msg::CheckUpdate => {
+                let result = match self.baseline.clone() {
+                    Some(baseline) => self.update_consensus_errors(baseline).map_err(|e| e.to_string()),
+                    None => Err(String::from("Load the previous version first.")),
+                };
+                self.update_check = Some((self.json_object.clone(), result));
+            },
+            Msg::UseCurrentAsBaseline => {
+                self.baseline = Some(self.document_types.clone());
+                self.baseline_settings = Some(self.settings.clone());
+                self.baseline_error = None;
+            },
+            Msg::ClearBaseline => {
+                self.baseline = None;
+                self.baseline_settings = None;
+                self.update_check = None;
+                self.baseline_error = None;
+            },
+
+            // Structural diff
+            Msg::SetDiffSource(side, source) => {
+                self.diff_sources[side] = source;
+            },
+            Msg::UpdateDiffText(side, text) => {
+                self.diff_texts[side] = text;
+            },
+
+            // Registration
+            Msg::UpdateOwnerId(owner_id) => {
+                self.owner_id = owner_id;
+            },
+            Msg::SetTransitionKind(kind) => {
+                self.transition_kind = kind;
+            },
+            Msg::UpdateContractId(contract_id) => {
+                self.contract_id = contract_id;
+            },
+            Msg::UpdateContractVersion(version) => {
+                self.contract_version = version;
+            },
+            Msg::UpdatePrivateKey(private_key) => {
+                self.private_key = private_key;
+            },
+            Msg::UpdateKeyId(key_id) => {
+                self.key_id = key_id;
+            },
+            Msg::BuildTransition => {
+                match self.build_transition() {
+                    Ok(transition) => {
+                        self.transition = Some(transition);
+                        self.transition_error = None;
+                    },
+                    Err(err) => {
+                        self.transition = None;
+                        self.transition_error = Some(err.to_string());
+                    },
+                }
+            },
+            Msg::SignTransition => {
+                let key_id = if self.key_id.trim().is_empty() { Ok(0) } else { self.key_id.trim().parse::<u32>() };
+                let result = match (&mut self.transition, key_id) {
+                    (Some(transition), Ok(key_id)) => registration::parse_private_key(&self.private_key)
+                        .and_then(|private_key| transition.sign(&private_key, key_id)),
+                    (_, Err(_)) => Err(anyhow::anyhow!("The key id must be a whole number.")),
+                    (None, _) => Ok(()),
+                };
+                self.transition_error = result.err().map(|e| e.to_string());
+            },
+            Msg::UpdateDapiEndpoint(endpoint) => {
+                self.workspace.dapi_endpoint = endpoint;
+            },
+            Msg::Broadcast => {
+                match self.transition.as_ref().map(|transition| transition.to_bytes()) {
+                    Some(Ok(bytes)) => {
+                        self.broadcasting = true;
+                        self.broadcast_status = None;
+                        let endpoint = self.workspace.dapi_endpoint.clone();
+                        let generation = self.generation;
+                        let callback = ctx.link().callback(move |result| Msg::ReceiveBroadcastResult(generation, result));
+                        spawn_local(async move {
+                            callback.emit(dapi::submit_state_transition(&endpoint, &bytes).await);
+                        });
+                    },
+                    Some(Err(err)) => {
+                        self.transition_error = Some(err.to_string());
+                    },
+                    None => {},
+                }
+            },
+            Msg::ReceiveBroadcastResult(_, result) => {
+                self.broadcasting = false;
+                match result {
+                    Ok(()) => {
+                        let contract_id = self.transition.as_ref().map(|transition| transition.contract_id()).unwrap_or_default();
+                        self.broadcast_status = Some(format!("Contract {} was accepted by Platform.", contract_id));
+                    },
+                    Err(dapi::DapiError::Rejected { code, message, data }) => {
+                        self.error_messages = self.consensus_error_messages(code, &message, data.as_deref());
+                        self.broadcast_status = Some(String::from("Platform rejected the transition. See the errors on the left."));
+                    },
+                    Err(dapi::DapiError::Other(err)) => {
+                        self.broadcast_status = Some(format!("Broadcast failed: {}", err));
+                    },
+                }
+            },
+            Msg::DownloadTransitionJson => {
+                if let Some(transition) = &self.transition {
+                    let filename = format!("{}-{}.json", files::file_stem(&self.workspace.projects[self.workspace.active].name), transition.kind().label());
+                    self.transition_error = transition.to_json()
+                        .and_then(|json| files::download(&filename, json.as_bytes(), "application/json"))
+                        .err()
+                        .map(|e| e.to_string());
+                }
+            },
+            Msg::DownloadTransitionBytes => {
+                if let Some(transition) = &self.transition {
+                    let filename = format!("{}-{}.bin", files::file_stem(&self.workspace.projects[self.workspace.active].name), transition.kind().label());
+                    self.transition_error = transition.to_bytes()
+                        .and_then(|bytes| files::download(&filename, &bytes, "application/octet-stream"))
+                        .err()
+                        .map(|e| e.to_string());
+                }
+            },
+        }
+        // Serialized bytes that no longer match the contract would be downloaded as if they did
+        if cbor_source.map_or(false, |source| source != self.cbor_source()) {
+            self.cbor = None;
+        }
+        if save {
+            self.save();
         }
         true
     }
@@ -2043,6 +4822,7 @@ impl Component for Model {
                             }
                         }
                     </div>
+                    {self.view_projects(ctx)}
                     <div class="columns">
                     <div class="column-left">
                     <div class="column-text">
@@ -2050,6 +4830,8 @@ impl Component for Model {
                     <p>{"Use the left column to build, edit, and submit a data contract."}</p>
                     </div>
 
+                        {self.view_contract_settings(ctx)}
+
                         // show input fields
                         {self.view_document_types(ctx)}
 
@@ -2057,10 +4839,16 @@ impl Component for Model {
                             // add input fields for another document type and add one to Self::document_types
                             <button class="button2" onclick={ctx.link().callback(|_| Msg::AddDocumentType)}><span>{"+"}</span>{"Add document type"}</button>
 
+                            <div class="undo-redo">
+                                <button class="button-clear" title="Undo (Ctrl+Z)" disabled={self.undo_stack.is_empty()} onclick={ctx.link().callback(|_| Msg::Undo)}>{"Undo"}</button>
+                                <button class="button-clear" title="Redo (Ctrl+Shift+Z)" disabled={self.redo_stack.is_empty()} onclick={ctx.link().callback(|_| Msg::Redo)}>{"Redo"}</button>
+                            </div>
+
                             // look at document_types and generate json object from it
                             <button class="button button-primary" onclick={ctx.link().callback(|_| Msg::Submit)}>{"Submit"}</button>
                         </div>
                         <div class="footnotes">
+                            <button class="button-clear wide" title="Clear this project's contract and prompt history" onclick={ctx.link().callback(|_| Msg::StartFresh)}>{"Start fresh"}</button>
                         </div>
                     </div>
                     <div class="column-right">
@@ -2070,7 +4858,15 @@ impl Component for Model {
                     </div>
                     
                         // format and display json object
-                      <div class="input-container">
+                      <div class={if self.drag_over {"input-container drag-over"} else {"input-container"}}
+                        ondragover={ctx.link().batch_callback(|e: DragEvent| { e.prevent_default(); Some(Msg::SetDragOver(true)) })}
+                        ondragleave={ctx.link().callback(|_: DragEvent| Msg::SetDragOver(false))}
+                        ondrop={ctx.link().batch_callback(|e: DragEvent| {
+                            e.prevent_default();
+                            let file = e.data_transfer().and_then(|data| data.files()).and_then(|files| files.get(0));
+                            Some(file.map(Msg::OpenContractFile).unwrap_or(Msg::SetDragOver(false)))
+                        })}
+                      >
                             <h2>{"Contract"}</h2>
                             <h3>{if self.imported_json.len() == 0 && self.error_messages.len() != 0 {"Validation errors:"} else {""}}</h3>
                             <div>{
@@ -2103,12 +4899,40 @@ impl Component for Model {
                               <button class="button-clear" onclick={ctx.link().callback(|_| Msg::Clear)}><span class="clear">{"X"}</span>{"Clear"}</button>
                               <button class="button-import" onclick={ctx.link().callback(|_| Msg::Import)}><img src="https://media.hellar.io/wp-content/uploads/arrow.down_.square.fill_.svg"/>{"Import"}</button>
                             </div>
-                        <div class="prompt-history">
-                            {if !self.history.is_empty() {html!{<h3>{"Prompt history:"}</h3>}} else {html!{""}}}
-                            {for self.history.iter().map(|input| html! {
-                                <div>{input}</div>
-                            })}
-                        </div>
+                            <div class="button-block">
+                              <label class="button-clear wide file-button" title="Or drop a .json file onto this panel">{"Open file"}
+                                <input type="file" accept=".json,application/json" onchange={ctx.link().batch_callback(|e: Event| {
+                                    let input = e.target_dyn_into::<web_sys::HtmlInputElement>().unwrap();
+                                    let file = input.files().and_then(|files| files.get(0));
+                                    input.set_value("");
+                                    file.map(Msg::OpenContractFile)
+                                })} />
+                              </label>
+                              <button class="button-clear wide" disabled={self.json_object.is_empty()} onclick={ctx.link().callback(|_| Msg::DownloadContract)}>{"Download JSON"}</button>
+                              <button class="button-clear wide" title="The document types with the contract settings" disabled={self.json_object.is_empty()} onclick={ctx.link().callback(|_| Msg::DownloadFullContract)}>{"Download full contract"}</button>
+                            </div>
+                            {if let Some(error) = &self.file_error {
+                                html! {<p class="error-text">{error.clone()}</p>}
+                            } else { html! {} }}
+                            {if !self.import_warnings.is_empty() {
+                                html! {
+                                    <>
+                                    <h3>{"Not imported:"}</h3>
+                                    <ul class="error-text">
+                                        { for self.import_warnings.iter().map(|warning| html! { <li>{warning.clone()}</li> }) }
+                                    </ul>
+                                    </>
+                                }
+                            } else { html! {} }}
+                            {self.view_network_import(ctx)}
+                            {self.view_cbor(ctx)}
+                            {self.view_registration(ctx)}
+                            {self.view_code(ctx)}
+                            {self.view_compatibility(ctx)}
+                            {self.view_diff(ctx)}
+                            {self.view_queries(ctx)}
+                            {self.view_diagram()}
+                        {self.view_history(ctx)}
                         </div>
                     </div>
                     </div>
@@ -2129,3 +4953,128 @@ fn main() {
     wasm_logger::init(wasm_logger::Config::default());
     yew::Renderer::<Model>::new().render();
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn import(documents: Value) -> (Vec<DocumentType>, Vec<String>) {
+        let parsed = documents.as_object().unwrap().clone().into_iter().collect();
+        let mut warnings = Vec::new();
+        let document_types = Model::parse_document_types(parsed, &mut warnings);
+        (document_types, warnings)
+    }
+
+    #[test]
+    fn extracts_whole_json() {
+        assert_eq!(extract_json_object("  {\"note\": {}}\n").as_deref(), Some("{\"note\": {}}"));
+    }
+
+    #[test]
+    fn extracts_json_from_commentary_with_braces() {
+        let text = "Objects are written as {key: value}. Use { to open one. Here it is: {\"note\":{\"type\":\"object\"}} Hope {this} helps!";
+        assert_eq!(extract_json_object(text).as_deref(), Some("{\"note\":{\"type\":\"object\"}}"));
+    }
+
+    #[test]
+    fn extracts_json_from_fenced_code_blocks() {
+        let text = "First some Rust:\n```rust\nfn main() {}\n```\nThen the contract {as requested}:\n```json\n{\n  \"note\": {\"type\": \"object\"}\n}\n```\nDone.";
+        assert_eq!(extract_json_object(text).as_deref(), Some("{\n  \"note\": {\"type\": \"object\"}\n}"));
+        let untagged = "```\n{\"note\": {}}\n```";
+        assert_eq!(extract_json_object(untagged).as_deref(), Some("{\"note\": {}}"));
+    }
+
+    #[test]
+    fn skips_braces_inside_strings() {
+        let contract = r#"{"note":{"description":"use } and { freely, even \"}\"","type":"object"}}"#;
+        let text = format!("Sure! {} Let me know.", contract);
+        assert_eq!(extract_json_object(&text).as_deref(), Some(contract));
+    }
+
+    #[test]
+    fn finds_no_json_object() {
+        assert_eq!(extract_json_object("No contract {here}, sorry."), None);
+        assert_eq!(extract_json_object("[1, 2, 3]"), None);
+        assert_eq!(extract_json_object("{\"unclosed\": \"value\""), None);
+    }
+
+    #[test]
+    fn parses_numeric_parameters() {
+        assert_eq!(parse_numeric(&DataType::Integer, " 42 "), Some(Number::from(42)));
+        assert_eq!(parse_numeric(&DataType::Integer, "1e3"), Some(Number::from(1000)));
+        assert_eq!(parse_numeric(&DataType::Integer, "1.5"), None);
+        assert_eq!(parse_numeric(&DataType::Integer, "1e30"), None);
+        assert_eq!(parse_numeric(&DataType::Number, "0.25"), Number::from_f64(0.25));
+        assert_eq!(parse_numeric(&DataType::Number, "abc"), None);
+        assert_eq!(parse_numeric(&DataType::Number, ""), None);
+    }
+
+    #[test]
+    fn imports_object_items() {
+        let (document_types, warnings) = import(json!({
+            "note": {"type": "object", "properties": {
+                "tags": {"type": "array", "items": {"type": "object", "properties": {
+                    "label": {"type": "string", "maxLength": 20},
+                    "weight": {"type": "integer"}
+                }, "required": ["label"]}}
+            }}
+        }));
+        assert!(warnings.is_empty());
+        let items = document_types[0].properties[0].items.as_ref().unwrap();
+        let fields = items.properties.as_ref().unwrap();
+        assert_eq!(fields.len(), 2);
+        let label = fields.iter().find(|field| field.name == "label").unwrap();
+        assert!(label.required);
+        assert_eq!(label.max_length, Some(20));
+    }
+
+    #[test]
+    fn warns_about_unsupported_items() {
+        let (document_types, warnings) = import(json!({
+            "note": {"type": "object", "properties": {
+                "matrix": {"type": "array", "items": {"type": "array", "items": {"type": "integer"}}},
+                "refs": {"type": "array", "items": {"$ref": "#/$defs/ref"}}
+            }}
+        }));
+        assert!(document_types[0].properties.iter().all(|property| property.items.is_none()));
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.iter().any(|warning| warning.starts_with("note.matrix.items: arrays of arrays")));
+        assert!(warnings.iter().any(|warning| warning.starts_with("note.refs.items:")));
+    }
+
+    #[test]
+    fn warns_about_nested_fields_of_items() {
+        let (document_types, warnings) = import(json!({
+            "note": {"type": "object", "properties": {
+                "tags": {"type": "array", "items": {"type": "object", "properties": {
+                    "label": {"type": "string"},
+                    "meta": {"type": "object", "properties": {}},
+                    "aliases": {"type": "array", "items": {"type": "string"}}
+                }}}
+            }}
+        }));
+        let fields = document_types[0].properties[0].items.as_ref().unwrap().properties.as_ref().unwrap();
+        assert_eq!(fields.len(), 1);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings.contains(&String::from("note.tags.items.meta: object fields of array items aren't supported, so the field was dropped")));
+        assert!(warnings.contains(&String::from("note.tags.items.aliases: array fields of array items aren't supported, so the field was dropped")));
+    }
+
+    #[test]
+    fn keeps_description_and_keywords_on_cbor_import() {
+        let documents = json!({"note": {"type": "object", "properties": {"message": {"type": "string"}}, "additionalProperties": false}});
+        let contract = Model::data_contract_factory().create(Identifier::default(), documents.into(), None, None).unwrap().data_contract;
+        let bytes = contract.to_cbor_buffer().unwrap();
+        let settings = ContractSettings { description: String::from(" Notes for everyone "), keywords: String::from("notes, social"), ..Default::default() };
+
+        let imported = serialization::contract_json_from_cbor(&bytes, &settings.metadata_json()).unwrap();
+        let imported_settings = Model::parse_contract_settings(&imported).unwrap();
+        assert_eq!(imported_settings.description, "Notes for everyone");
+        assert_eq!(imported_settings.keywords, "notes, social");
+        assert!(Model::parse_contract_documents(&imported).unwrap().contains_key("note"));
+
+        let bare = serialization::contract_json_from_cbor(&bytes, &Map::new()).unwrap();
+        let bare_settings = Model::parse_contract_settings(&bare).unwrap();
+        assert!(bare_settings.description.is_empty() && bare_settings.keywords.is_empty());
+    }
+}
@@ -0,0 +1,457 @@
+//! Checks which document queries the indices of a document type can serve, following the rules
+//! Platform applies to queries: equality conditions on a prefix of an index, at most one range
+//! condition on the next index property, and orderBy continuing the index. Platform only accepts
+//! ascending index properties, so every index is treated as ascending and walked forwards or backwards.
+
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use crate::{codegen::to_pascal_case, indexing, registration, DataType, DocumentType, Index, IndexProperties};
+
+/// A where clause operator
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Operator {
+    Equal,
+    In,
+    GreaterThan,
+    GreaterThanOrEqual,
+    LessThan,
+    LessThanOrEqual,
+    StartsWith,
+}
+
+impl Operator {
+    pub const ALL: [Operator; 7] = [
+        Operator::Equal,
+        Operator::In,
+        Operator::GreaterThan,
+        Operator::GreaterThanOrEqual,
+        Operator::LessThan,
+        Operator::LessThanOrEqual,
+        Operator::StartsWith,
+    ];
+
+    /// The operator as written in a query
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Operator::Equal => "==",
+            Operator::In => "in",
+            Operator::GreaterThan => ">",
+            Operator::GreaterThanOrEqual => ">=",
+            Operator::LessThan => "<",
+            Operator::LessThanOrEqual => "<=",
+            Operator::StartsWith => "startsWith",
+        }
+    }
+
+    fn is_lower_bound(&self) -> bool {
+        matches!(self, Operator::GreaterThan | Operator::GreaterThanOrEqual)
+    }
+
+    fn is_upper_bound(&self) -> bool {
+        matches!(self, Operator::LessThan | Operator::LessThanOrEqual)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WhereClause {
+    pub field: String,
+    pub operator: Operator,
+}
+
+impl Default for WhereClause {
+    fn default() -> Self {
+        Self { field: String::new(), operator: Operator::Equal }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OrderBy {
+    pub field: String,
+    pub ascending: bool,
+}
+
+impl Default for OrderBy {
+    fn default() -> Self {
+        Self { field: String::new(), ascending: true }
+    }
+}
+
+/// A query the app will run against one document type. Values are left out, since they don't affect which index is used.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Query {
+    pub document_type: String,
+    pub where_clauses: Vec<WhereClause>,
+    pub order_by: Vec<OrderBy>,
+    /// Id of the document to start from, for pagination
+    pub start_at: String,
+}
+
+/// Whether and how the document type can serve a query
+#[derive(Debug, Clone)]
+pub enum Plan {
+    /// The query breaks Platform's query rules, whatever the indices
+    Invalid(Vec<String>),
+    /// Without conditions or ordering, documents are read in `$id` order and no index is needed
+    ById,
+    /// Served by the index at this position in the document type's indices
+    Index(usize),
+    /// No index serves the query. This one would.
+    Missing(Index),
+}
+
+/// The fields of a valid query, in the order an index has to list them
+struct Shape<'a> {
+    /// Fields compared with `==` or `in`, which an index has to start with, in any order
+    prefix: Vec<&'a str>,
+    /// The range field and the orderBy fields that follow the prefix, in order
+    tail: Vec<&'a str>,
+}
+
+/// Checks the query against Platform's rules and works out its shape
+fn shape<'a>(document_type: &DocumentType, query: &'a Query) -> Result<Shape<'a>, Vec<String>> {
+    let mut errors = Vec::new();
+    let paths = indexing::indexable_paths(document_type);
+    let mut prefix: Vec<&str> = Vec::new();
+    let mut in_field: Option<&str> = None;
+    let mut range_field: Option<&str> = None;
+
+    for (number, clause) in query.where_clauses.iter().enumerate() {
+        let field = clause.field.as_str();
+        if field.is_empty() {
+            errors.push(format!("Choose a field for where clause {}.", number + 1));
+            continue;
+        }
+        if !paths.iter().any(|path| path == field) {
+            errors.push(format!("\"{}\" can't be queried, since it isn't an indexable field.", field));
+            continue;
+        }
+        let others: Vec<Operator> = query.where_clauses.iter()
+            .enumerate()
+            .filter(|(other, other_clause)| *other != number && other_clause.field == field)
+            .map(|(_, other_clause)| other_clause.operator)
+            .collect();
+        match clause.operator {
+            Operator::Equal | Operator::In if !others.is_empty() => {
+                errors.push(format!("\"{}\" can only have one condition when compared with {}.", field, clause.operator.symbol()));
+            },
+            Operator::Equal => prefix.push(field),
+            Operator::In => {
+                if in_field.is_some() {
+                    errors.push(String::from("Only one \"in\" condition is allowed per query."));
+                }
+                in_field = Some(field);
+                prefix.push(field);
+            },
+            Operator::StartsWith if !others.is_empty() => {
+                errors.push(format!("\"{}\" can't be combined with other conditions when using startsWith.", field));
+            },
+            operator => {
+                // A lower and an upper bound on the same field together make a single range
+                let combinable = others.iter().all(|other| {
+                    operator.is_lower_bound() && other.is_upper_bound() || operator.is_upper_bound() && other.is_lower_bound()
+                });
+                if others.len() > 1 || !combinable {
+                    errors.push(format!("\"{}\" can only have one lower and one upper bound.", field));
+                }
+                let is_string = indexing::find_property(&document_type.properties, field)
+                    .map_or(false, |property| property.data_type == DataType::String);
+                if operator == Operator::StartsWith && !is_string {
+                    errors.push(format!("startsWith only works on string properties, not \"{}\".", field));
+                }
+                match range_field {
+                    Some(range) if range != field => {
+                        errors.push(format!("Range conditions are only allowed on one field, but \"{}\" and \"{}\" both have one.", range, field));
+                    },
+                    _ => range_field = Some(field),
+                }
+            },
+        }
+    }
+
+    let mut ordered: Vec<&str> = Vec::new();
+    for (number, order) in query.order_by.iter().enumerate() {
+        let field = order.field.as_str();
+        if field.is_empty() {
+            errors.push(format!("Choose a field for orderBy {}.", number + 1));
+        } else if !paths.iter().any(|path| path == field) {
+            errors.push(format!("\"{}\" can't be ordered by, since it isn't an indexable field.", field));
+        } else if ordered.contains(&field) {
+            errors.push(format!("\"{}\" is ordered by twice.", field));
+        } else {
+            ordered.push(field);
+        }
+    }
+    if let (Some(range), Some(first)) = (range_field, query.order_by.first()) {
+        if first.field != range {
+            errors.push(format!("With a range condition on \"{}\", orderBy has to start with \"{}\".", range, range));
+        }
+    }
+    // Indices are ascending and can be walked backwards, so the fields after the prefix have to share a direction
+    let tail_directions: Vec<bool> = query.order_by.iter()
+        .filter(|order| !prefix.contains(&order.field.as_str()))
+        .map(|order| order.ascending)
+        .collect();
+    if tail_directions.windows(2).any(|pair| pair[0] != pair[1]) {
+        errors.push(String::from("Fields ordered by after the equality conditions must all be ascending or all descending."));
+    }
+    if !query.start_at.trim().is_empty() {
+        if let Err(err) = registration::parse_identifier(&query.start_at, "startAt document id") {
+            errors.push(err.to_string());
+        }
+    }
+    if !errors.is_empty() {
+        // Both clauses of a conflicting pair report the same problem
+        let mut unique: Vec<String> = Vec::new();
+        for error in errors {
+            if !unique.contains(&error) {
+                unique.push(error);
+            }
+        }
+        return Err(unique);
+    }
+
+    let tail = range_field.into_iter()
+        .chain(ordered.into_iter().filter(|field| !prefix.contains(field) && Some(*field) != range_field))
+        .collect();
+    Ok(Shape { prefix, tail })
+}
+
+/// How many index properties are left over after serving a query of this shape, if the index serves it
+fn unused_properties(index: &Index, shape: &Shape) -> Option<usize> {
+    let names: Vec<&str> = index.properties.iter().map(|property| property.name.as_str()).collect();
+    let used = shape.prefix.len() + shape.tail.len();
+    if names.len() < used {
+        return None;
+    }
+    let (head, rest) = names.split_at(shape.prefix.len());
+    let prefix_matches = head.iter().all(|name| shape.prefix.contains(name));
+    (prefix_matches && rest[..shape.tail.len()] == shape.tail[..]).then(|| names.len() - used)
+}
+
+/// Fields the index lists, in order, to serve the query
+fn suggested_fields<'a>(shape: &Shape<'a>) -> Vec<&'a str> {
+    shape.prefix.iter().chain(shape.tail.iter()).copied().collect()
+}
+
+/// A name for an index over the fields, e.g. `ownerIdCreatedAt`, not yet used in the document type
+pub fn index_name(fields: &[&str], document_type: &DocumentType) -> String {
+    let joined: String = fields.iter().map(|field| to_pascal_case(&field.replace(['$', '.'], "_"))).collect();
+    let mut chars = joined.chars();
+    let base = match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect::<String>(),
+        None => String::from("index"),
+    };
+    let mut name = base.clone();
+    let mut number = 2;
+    while document_type.indices.iter().any(|index| index.name == name) {
+        name = format!("{}{}", base, number);
+        number += 1;
+    }
+    name
+}
+
+/// A non-unique, ascending index over the fields
+pub fn new_index(fields: &[&str], document_type: &DocumentType) -> Index {
+    Index {
+        name: index_name(fields, document_type),
+        properties: fields.iter()
+            .map(|field| IndexProperties { name: field.to_string(), order: String::from("asc") })
+            .collect(),
+        unique: false,
+        null_searchable: None,
+    }
+}
+
+/// Works out which index of the document type serves the query, preferring the one with the fewest unused properties
+pub fn plan(document_type: &DocumentType, query: &Query) -> Plan {
+    let shape = match shape(document_type, query) {
+        Ok(shape) => shape,
+        Err(errors) => return Plan::Invalid(errors),
+    };
+    if shape.prefix.is_empty() && shape.tail.is_empty() {
+        return Plan::ById;
+    }
+    let best = document_type.indices.iter()
+        .enumerate()
+        .filter_map(|(position, index)| unused_properties(index, &shape).map(|unused| (position, unused)))
+        .min_by_key(|(_, unused)| *unused);
+    match best {
+        Some((position, _)) => Plan::Index(position),
+        None => Plan::Missing(new_index(&suggested_fields(&shape), document_type)),
+    }
+}
+
+/// Most indices a document type may have
+pub const MAX_INDICES: usize = 10;
+
+/// Most properties an index may have
+pub const MAX_INDEX_PROPERTIES: usize = 10;
+
+/// Indices to add so a document type serves all of its declared queries
+#[derive(Debug, Clone, Default)]
+pub struct Suggestion {
+    pub indices: Vec<Index>,
+    /// Queries that can't be served, and why
+    pub warnings: Vec<String>,
+}
+
+/// Works out the fewest indices that, with the existing ones, serve the document type's queries.
+///
+/// Equality fields are ordered by how many queries use them, so queries sharing fields share an
+/// index prefix, and longer queries are placed first, so shorter ones are served by their prefixes.
+pub fn suggest_indices(document_type: &DocumentType, queries: &[Query]) -> Suggestion {
+    let mut suggestion = Suggestion::default();
+    let mut shapes = Vec::new();
+    for (number, query) in queries.iter().enumerate().filter(|(_, query)| query.document_type == document_type.name) {
+        match shape(document_type, query) {
+            Ok(shape) if !shape.prefix.is_empty() || !shape.tail.is_empty() => shapes.push((number, shape)),
+            Ok(_) => {},
+            Err(errors) => suggestion.warnings.push(format!("Query {} is skipped: {}", number + 1, errors.join(" "))),
+        }
+    }
+
+    let mut frequency: HashMap<&str, usize> = HashMap::new();
+    for field in shapes.iter().flat_map(|(_, shape)| shape.prefix.iter()) {
+        *frequency.entry(field).or_default() += 1;
+    }
+    for (_, shape) in shapes.iter_mut() {
+        // A stable sort keeps the order of the query's own clauses between equally common fields
+        shape.prefix.sort_by_key(|field| std::cmp::Reverse(frequency[field]));
+    }
+    shapes.sort_by_key(|(_, shape)| std::cmp::Reverse(shape.prefix.len() + shape.tail.len()));
+
+    // New indices are collected on a copy, so later queries are checked against them and their names stay unique
+    let mut extended = document_type.clone();
+    for (number, shape) in &shapes {
+        if extended.indices.iter().any(|index| unused_properties(index, shape).is_some()) {
+            continue;
+        }
+        let fields = suggested_fields(shape);
+        if fields.len() > MAX_INDEX_PROPERTIES {
+            suggestion.warnings.push(format!("Query {} needs an index over {} properties, but indices can have at most {}.", number + 1, fields.len(), MAX_INDEX_PROPERTIES));
+            continue;
+        }
+        if extended.indices.len() >= MAX_INDICES {
+            suggestion.warnings.push(format!("Query {} needs another index, but document types can have at most {}.", number + 1, MAX_INDICES));
+            continue;
+        }
+        let index = new_index(&fields, &extended);
+        extended.indices.push(index.clone());
+        suggestion.indices.push(index);
+    }
+    suggestion
+}
+
+/// Warnings for the queries whose document type doesn't exist, which no index suggestion covers
+pub fn unknown_document_types(document_types: &[DocumentType], queries: &[Query]) -> Vec<String> {
+    queries.iter()
+        .enumerate()
+        .filter(|(_, query)| !document_types.iter().any(|document_type| document_type.name == query.document_type))
+        .map(|(number, query)| format!("Query {} is skipped: there is no document type named \"{}\".", number + 1, query.document_type))
+        .collect()
+}
+
+/// Parses queries written one per line, e.g. `post where $ownerId == and score > orderBy score desc`.
+/// Lines starting with `#` and blank lines are skipped.
+pub fn parse_queries(text: &str) -> Result<Vec<Query>, String> {
+    let mut queries = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        queries.push(parse_query(line).map_err(|err| format!("Line {}: {}", number + 1, err))?);
+    }
+    Ok(queries)
+}
+
+fn parse_query(line: &str) -> Result<Query, String> {
+    let tokens: Vec<&str> = line.split(|c: char| c.is_whitespace() || c == ',').filter(|token| !token.is_empty()).collect();
+    let mut tokens = tokens.into_iter().peekable();
+    let mut query = Query {
+        document_type: tokens.next().ok_or("expected a document type")?.to_string(),
+        ..Default::default()
+    };
+    while let Some(keyword) = tokens.next() {
+        match keyword {
+            "where" | "and" => {
+                let field = tokens.next().ok_or_else(|| format!("expected a field after \"{}\"", keyword))?;
+                let symbol = tokens.next().ok_or_else(|| format!("expected an operator after \"{}\"", field))?;
+                let operator = Operator::ALL.iter()
+                    .find(|operator| operator.symbol() == symbol)
+                    .ok_or_else(|| format!("unknown operator \"{}\"", symbol))?;
+                query.where_clauses.push(WhereClause { field: field.to_string(), operator: *operator });
+            },
+            "orderBy" => loop {
+                let field = tokens.next().ok_or("expected a field after \"orderBy\"")?;
+                let ascending = match tokens.peek() {
+                    Some(&"asc") => { tokens.next(); true },
+                    Some(&"desc") => { tokens.next(); false },
+                    _ => true,
+                };
+                query.order_by.push(OrderBy { field: field.to_string(), ascending });
+                if !matches!(tokens.peek(), Some(token) if !["where", "and", "orderBy", "startAt"].contains(token)) {
+                    break;
+                }
+            },
+            "startAt" => {
+                query.start_at = tokens.next().ok_or("expected a document id after \"startAt\"")?.to_string();
+            },
+            other => return Err(format!("expected \"where\", \"and\", \"orderBy\" or \"startAt\", not \"{}\"", other)),
+        }
+    }
+    Ok(query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Property;
+
+    fn post(indices: Vec<Index>) -> DocumentType {
+        let property = |name: &str, data_type| Property { name: name.to_string(), data_type, max_length: Some(63), ..Default::default() };
+        DocumentType {
+            name: String::from("post"),
+            properties: vec![property("topic", DataType::String), property("score", DataType::Integer), property("title", DataType::String)],
+            indices,
+            ..Default::default()
+        }
+    }
+
+    fn index(properties: &[(&str, &str)]) -> Index {
+        Index {
+            name: String::from("index"),
+            properties: properties.iter()
+                .map(|(name, order)| IndexProperties { name: name.to_string(), order: order.to_string() })
+                .collect(),
+            unique: false,
+            null_searchable: None,
+        }
+    }
+
+    fn query(text: &str) -> Query {
+        parse_query(text).unwrap()
+    }
+
+    #[test]
+    fn walks_indices_forwards_and_backwards() {
+        let document_type = post(vec![index(&[("topic", "asc"), ("score", "asc"), ("title", "asc")])]);
+        assert!(matches!(plan(&document_type, &query("post where topic == orderBy score asc title asc")), Plan::Index(0)));
+        assert!(matches!(plan(&document_type, &query("post where topic == orderBy score desc title desc")), Plan::Index(0)));
+        assert!(matches!(plan(&document_type, &query("post where score > orderBy score desc")), Plan::Missing(_)));
+    }
+
+    #[test]
+    fn names_the_keyword_of_a_missing_field() {
+        assert_eq!(parse_query("post where").unwrap_err(), "expected a field after \"where\"");
+        assert_eq!(parse_query("post where topic == and").unwrap_err(), "expected a field after \"and\"");
+    }
+
+    #[test]
+    fn warns_about_unknown_document_types() {
+        let document_types = [post(vec![])];
+        let queries = [query("post where topic =="), query("comment where topic ==")];
+        assert_eq!(unknown_document_types(&document_types, &queries), ["Query 2 is skipped: there is no document type named \"comment\"."]);
+    }
+}
@@ -0,0 +1,262 @@
+//! Builds the state transitions that register or update a contract on Platform, signs them
+//! with a locally entered identity key, and exports them as JSON and serialized bytes.
+
+use dpp::{
+    bls::BlsModule,
+    data_contract::state_transition::{
+        data_contract_create_transition::DataContractCreateTransition,
+        data_contract_update_transition::DataContractUpdateTransition,
+    },
+    identity::{KeyID, KeyType},
+    platform_value::string_encoding::Encoding,
+    prelude::Identifier,
+    state_transition::{StateTransitionConvert, StateTransitionIdentitySigned},
+    ProtocolError, PublicKeyValidationError,
+};
+use sha2::{Digest, Sha256};
+
+/// Parses an identifier, given in base58 as Platform shows them, or as 64 hex characters.
+/// `what` names the identifier in error messages.
+pub fn parse_identifier(text: &str, what: &str) -> Result<Identifier, anyhow::Error> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(anyhow::anyhow!("Enter the {}.", what));
+    }
+    let encoding = if text.len() == 64 && text.chars().all(|c| c.is_ascii_hexdigit()) { Encoding::Hex } else { Encoding::Base58 };
+    Identifier::from_string(text, encoding).map_err(|e| anyhow::anyhow!("Invalid {}: {}", what, e))
+}
+
+/// WIF version bytes of Hellar mainnet and of testnet, devnets and regtest
+const WIF_VERSIONS: [u8; 2] = [0xcc, 0xef];
+
+/// Decodes an ECDSA private key given as WIF or as 64 hex characters
+pub fn parse_private_key(text: &str) -> Result<[u8; 32], anyhow::Error> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(anyhow::anyhow!("Enter the private key of an identity key."));
+    }
+    let key = if text.len() == 64 && text.chars().all(|c| c.is_ascii_hexdigit()) {
+        hex::decode(text)?
+    } else {
+        // WIF: version byte, 32-byte key, optional compression flag, then a 4-byte double SHA-256 checksum
+        let bytes = bs58::decode(text).into_vec().map_err(|_| anyhow::anyhow!("The private key is neither WIF nor hex."))?;
+        if bytes.len() < 5 {
+            return Err(anyhow::anyhow!("The private key is too short."));
+        }
+        let (payload, checksum) = bytes.split_at(bytes.len() - 4);
+        if &Sha256::digest(Sha256::digest(payload))[..4] != checksum {
+            return Err(anyhow::anyhow!("The WIF checksum doesn't match. Check the key for typos."));
+        }
+        if !WIF_VERSIONS.contains(&payload[0]) {
+            return Err(anyhow::anyhow!("The WIF is for another network. Use a Hellar mainnet or testnet key."));
+        }
+        match payload.len() {
+            33 => payload[1..].to_vec(),
+            34 if payload[33] == 1 => payload[1..33].to_vec(),
+            _ => return Err(anyhow::anyhow!("The WIF doesn't hold a 32-byte private key.")),
+        }
+    };
+    key.try_into().map_err(|_| anyhow::anyhow!("The private key must be 32 bytes."))
+}
+
+/// Only ECDSA keys are supported, so BLS operations fail
+struct NoBls;
+
+impl BlsModule for NoBls {
+    fn validate_public_key(&self, _pk: &[u8]) -> Result<(), PublicKeyValidationError> {
+        Ok(())
+    }
+
+    fn verify_signature(&self, _signature: &[u8], _data: &[u8], _public_key: &[u8]) -> Result<bool, ProtocolError> {
+        Err(ProtocolError::Generic(String::from("BLS keys are not supported")))
+    }
+
+    fn private_key_to_public_key(&self, _private_key: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+        Err(ProtocolError::Generic(String::from("BLS keys are not supported")))
+    }
+
+    fn sign(&self, _data: &[u8], _private_key: &[u8]) -> Result<Vec<u8>, ProtocolError> {
+        Err(ProtocolError::Generic(String::from("BLS keys are not supported")))
+    }
+}
+
+/// Whether the transition registers a new contract or updates a registered one
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TransitionKind {
+    Create,
+    Update,
+}
+
+impl TransitionKind {
+    pub const ALL: [TransitionKind; 2] = [TransitionKind::Create, TransitionKind::Update];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TransitionKind::Create => "DataContractCreate",
+            TransitionKind::Update => "DataContractUpdate",
+        }
+    }
+}
+
+pub enum ContractTransition {
+    Create(DataContractCreateTransition),
+    Update(DataContractUpdateTransition),
+}
+
+/// A built state transition, ready to sign and export
+pub struct Transition {
+    pub transition: ContractTransition,
+    /// The contract JSON it was built from, to tell when it is out of date
+    pub source: Vec<String>,
+    /// Id of the identity key that signed it, if signed
+    pub signed_by: Option<KeyID>,
+}
+
+fn sign_transition(transition: &mut impl StateTransitionIdentitySigned, private_key: &[u8], key_id: KeyID) -> Result<(), ProtocolError> {
+    transition.set_signature_public_key_id(key_id);
+    transition.sign_by_private_key(private_key, KeyType::ECDSA_SECP256K1, &NoBls)
+}
+
+impl Transition {
+    pub fn new(transition: ContractTransition, source: Vec<String>) -> Self {
+        Self { transition, source, signed_by: None }
+    }
+
+    pub fn kind(&self) -> TransitionKind {
+        match self.transition {
+            ContractTransition::Create(_) => TransitionKind::Create,
+            ContractTransition::Update(_) => TransitionKind::Update,
+        }
+    }
+
+    /// Id of the contract the transition registers or updates, in base58
+    pub fn contract_id(&self) -> String {
+        match &self.transition {
+            ContractTransition::Create(transition) => transition.data_contract.id.to_string(Encoding::Base58),
+            ContractTransition::Update(transition) => transition.data_contract.id.to_string(Encoding::Base58),
+        }
+    }
+
+    /// Signs the transition with an ECDSA identity key of the contract owner
+    pub fn sign(&mut self, private_key: &[u8; 32], key_id: KeyID) -> Result<(), anyhow::Error> {
+        let result = match &mut self.transition {
+            ContractTransition::Create(transition) => sign_transition(transition, private_key, key_id),
+            ContractTransition::Update(transition) => sign_transition(transition, private_key, key_id),
+        };
+        result.map_err(|e| anyhow::anyhow!("Failed to sign the transition: {}", e))?;
+        self.signed_by = Some(key_id);
+        Ok(())
+    }
+
+    /// The transition as pretty-printed JSON, with the signature once signed
+    pub fn to_json(&self) -> Result<String, anyhow::Error> {
+        let skip_signature = self.signed_by.is_none();
+        let json = match &self.transition {
+            ContractTransition::Create(transition) => transition.to_json(skip_signature),
+            ContractTransition::Update(transition) => transition.to_json(skip_signature),
+        };
+        let json = json.map_err(|e| anyhow::anyhow!("Failed to convert the transition to JSON: {}", e))?;
+        Ok(serde_json::to_string_pretty(&json)?)
+    }
+
+    /// The transition serialized with Platform's encoding, with the signature once signed
+    pub fn to_bytes(&self) -> Result<Vec<u8>, anyhow::Error> {
+        let skip_signature = self.signed_by.is_none();
+        let bytes = match &self.transition {
+            ContractTransition::Create(transition) => transition.to_buffer(skip_signature),
+            ContractTransition::Update(transition) => transition.to_buffer(skip_signature),
+        };
+        bytes.map_err(|e| anyhow::anyhow!("Failed to serialize the transition: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The private key that all the vectors below encode
+    const KEY_HEX: &str = "0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20";
+
+    fn key() -> [u8; 32] {
+        hex::decode(KEY_HEX).unwrap().try_into().unwrap()
+    }
+
+    fn error(text: &str) -> String {
+        parse_private_key(text).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn decodes_hex_keys() {
+        assert_eq!(parse_private_key(KEY_HEX).unwrap(), key());
+        assert_eq!(parse_private_key(&format!("  {}\n", KEY_HEX.to_uppercase())).unwrap(), key());
+    }
+
+    #[test]
+    fn decodes_compressed_testnet_wif() {
+        assert_eq!(parse_private_key("cMcfH8sRgBgDMfpBNG6H3haaxLkaYXgqMRef8Nev6tWyBSNr6c3n").unwrap(), key());
+    }
+
+    #[test]
+    fn decodes_uncompressed_mainnet_wif() {
+        assert_eq!(parse_private_key("7qZJjUhYPcUxZkabfeu7cUvpQbNhBAndQ6re444mAKcMhAnezWG").unwrap(), key());
+    }
+
+    #[test]
+    fn rejects_a_bad_checksum() {
+        assert!(error("cMcfH8sRgBgDMfpBNG6H3haaxLkaYXgqMRef8Nev6tWyBSNsabL8").contains("checksum"));
+    }
+
+    #[test]
+    fn rejects_a_wrong_length() {
+        // Valid checksum around a 31-byte key
+        assert!(error("2pG323MmTPvDXTwfc72qFtQmjj8DDeSteGFpVAKcLKKLXaiYGN").contains("32-byte"));
+        // 62 hex characters aren't a hex key, and '0' isn't base58
+        assert!(error(&KEY_HEX[..62]).contains("neither WIF nor hex"));
+        assert!(error("").contains("Enter"));
+    }
+
+    #[test]
+    fn rejects_a_wrong_network_byte() {
+        // The same key as a Bitcoin mainnet WIF, version byte 0x80
+        assert!(error("KwFfpDsaF7yxCELuyrH9gP5XL7TAt5b9HPWC1xCQbmrxvhJgMQHb").contains("another network"));
+    }
+
+    fn fixed_transition() -> Transition {
+        let documents = serde_json::json!({
+            "note": {
+                "type": "object",
+                "properties": { "message": { "type": "string", "maxLength": 63 } },
+                "additionalProperties": false,
+            }
+        });
+        let owner_id = Identifier::from_string("11111111111111111111111111111111", Encoding::Base58).unwrap();
+        let mut data_contract = crate::Model::data_contract_factory().create(owner_id, documents.into(), None, None).unwrap().data_contract;
+        data_contract.id = owner_id;
+        data_contract.version = 2;
+        let transition = DataContractUpdateTransition {
+            protocol_version: dpp::version::LATEST_VERSION,
+            data_contract,
+            ..Default::default()
+        };
+        Transition::new(ContractTransition::Update(transition), Vec::new())
+    }
+
+    #[test]
+    fn signs_a_fixed_transition_deterministically() {
+        let unsigned = fixed_transition().to_bytes().unwrap();
+        let mut signed = fixed_transition();
+        signed.sign(&key(), 2).unwrap();
+        assert_eq!(signed.signed_by, Some(2));
+        let signed_bytes = signed.to_bytes().unwrap();
+        assert_ne!(signed_bytes, unsigned);
+
+        // ECDSA signatures are deterministic (RFC 6979), so the same key signs identically
+        let mut again = fixed_transition();
+        again.sign(&key(), 2).unwrap();
+        assert_eq!(again.to_bytes().unwrap(), signed_bytes);
+
+        let mut other_key = fixed_transition();
+        other_key.sign(&[7; 32], 2).unwrap();
+        assert_ne!(other_key.to_bytes().unwrap(), signed_bytes);
+    }
+}
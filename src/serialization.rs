@@ -0,0 +1,125 @@
+//! Binary serialization of data contracts: Platform's CBOR encoding, shown as hex or base64.
+
+use base64::{Engine as _, engine::general_purpose::STANDARD as BASE64};
+use dpp::{data_contract::DataContract, Convertible};
+use serde_json::{json, Map, Value};
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    hex::encode(bytes)
+}
+
+pub fn to_base64(bytes: &[u8]) -> String {
+    BASE64.encode(bytes)
+}
+
+/// How text to import encodes its bytes
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum TextEncoding {
+    /// Hex if the text is valid hex, base64 otherwise
+    #[default]
+    Auto,
+    Hex,
+    Base64,
+}
+
+impl TextEncoding {
+    pub const ALL: [TextEncoding; 3] = [TextEncoding::Auto, TextEncoding::Hex, TextEncoding::Base64];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TextEncoding::Auto => "Detect",
+            TextEncoding::Hex => "Hex",
+            TextEncoding::Base64 => "Base64",
+        }
+    }
+}
+
+/// Decodes hex or base64 text, ignoring whitespace. Base64 made only of hex digits is also valid hex,
+/// so `Auto` reads it as hex and the encoding has to be chosen to read it as base64.
+pub fn decode_text(text: &str, encoding: TextEncoding) -> Result<Vec<u8>, anyhow::Error> {
+    let compact: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if compact.is_empty() {
+        return Err(anyhow::anyhow!("Nothing to decode."));
+    }
+    let hex = || hex::decode(compact.strip_prefix("0x").unwrap_or(&compact));
+    match encoding {
+        TextEncoding::Hex => hex().map_err(|e| anyhow::anyhow!("The text isn't valid hex: {}", e)),
+        TextEncoding::Base64 => BASE64.decode(&compact).map_err(|e| anyhow::anyhow!("The text isn't valid base64: {}", e)),
+        TextEncoding::Auto => hex()
+            .or_else(|_| BASE64.decode(&compact))
+            .map_err(|_| anyhow::anyhow!("The text is neither valid hex nor valid base64.")),
+    }
+}
+
+/// Decodes a CBOR-serialized data contract and returns it as full contract JSON text, ready for the importer
+pub fn contract_json_from_cbor(bytes: &[u8], metadata: &Map<String, Value>) -> Result<String, anyhow::Error> {
+    contract_json(&contract_from_cbor(bytes)?, metadata)
+}
+
+pub fn contract_from_cbor(bytes: &[u8]) -> Result<DataContract, anyhow::Error> {
+    DataContract::from_cbor(bytes).map_err(|e| anyhow::anyhow!("Not a CBOR-serialized data contract: {}", e))
+}
+
+/// The config and document types of a data contract as JSON text, ready for the importer.
+/// DPP doesn't carry the description and keywords, so they come from `metadata` instead.
+pub fn contract_json(contract: &DataContract, metadata: &Map<String, Value>) -> Result<String, anyhow::Error> {
+    let json = contract.to_json()
+        .map_err(|e| anyhow::anyhow!("Failed to convert the data contract to JSON: {}", e))?;
+    let documents = json.get("documents")
+        .ok_or_else(|| anyhow::anyhow!("The data contract has no document types."))?;
+    let config = &contract.config;
+    let mut full = Map::new();
+    full.insert("config".to_owned(), json!({
+        "canBeDeleted": config.can_be_deleted,
+        "readonly": config.readonly,
+        "keepsHistory": config.keeps_history,
+        "documentsKeepHistoryContractDefault": config.documents_keep_history_contract_default,
+        "documentsMutableContractDefault": config.documents_mutable_contract_default,
+    }));
+    full.extend(metadata.clone());
+    full.insert("documents".to_owned(), documents.clone());
+    Ok(Value::Object(full).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dpp::prelude::Identifier;
+
+    #[test]
+    fn round_trips_hex_and_base64() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        assert_eq!(decode_text(&to_hex(&bytes), TextEncoding::Auto).unwrap(), bytes);
+        assert_eq!(decode_text(&to_hex(&bytes), TextEncoding::Hex).unwrap(), bytes);
+        assert_eq!(decode_text(&to_base64(&bytes), TextEncoding::Auto).unwrap(), bytes);
+        assert_eq!(decode_text(&to_base64(&bytes), TextEncoding::Base64).unwrap(), bytes);
+        assert_eq!(decode_text(" 0x00ff\n10 ", TextEncoding::Auto).unwrap(), [0x00, 0xff, 0x10]);
+    }
+
+    #[test]
+    fn respects_the_chosen_encoding() {
+        // "abcd" is valid hex and valid base64
+        assert_eq!(decode_text("abcd", TextEncoding::Auto).unwrap(), [0xab, 0xcd]);
+        assert_eq!(decode_text("abcd", TextEncoding::Base64).unwrap(), [0x69, 0xb7, 0x1d]);
+        assert!(decode_text("abc=", TextEncoding::Hex).is_err());
+        assert!(decode_text("ab-d", TextEncoding::Base64).is_err());
+        assert!(decode_text("  ", TextEncoding::Auto).is_err());
+    }
+
+    #[test]
+    fn round_trips_contracts_through_cbor() {
+        let documents = json!({"note": {"type": "object", "properties": {"message": {"type": "string", "maxLength": 63}}, "additionalProperties": false}});
+        let contract = crate::Model::data_contract_factory()
+            .create(Identifier::default(), documents.clone().into(), None, None)
+            .unwrap()
+            .data_contract;
+        let bytes = contract.to_cbor_buffer().unwrap();
+        let decoded = decode_text(&to_base64(&bytes), TextEncoding::Base64).unwrap();
+        assert_eq!(decoded, bytes);
+
+        let imported: Value = serde_json::from_str(&contract_json_from_cbor(&decoded, &Map::new()).unwrap()).unwrap();
+        assert_eq!(imported["documents"], documents);
+        assert!(imported["config"].is_object());
+        assert!(contract_from_cbor(&[0xff, 0x00]).is_err());
+    }
+}
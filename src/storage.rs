@@ -0,0 +1,142 @@
+//! Autosaves the projects to the browser's local storage, so refreshing the page doesn't lose work.
+
+use serde::{Serialize, Deserialize};
+use crate::{query::Query, ContractSettings, DocumentType, HistoryEntry};
+
+/// Local storage key of the workspace. Bump the version if `Workspace` changes incompatibly.
+const WORKSPACE_KEY: &str = "data-contract-creator/workspace/v1";
+
+/// Local storage key used before projects existed. A draft found there becomes the first project.
+const LEGACY_DRAFT_KEY: &str = "data-contract-creator/draft/v1";
+
+/// Marks exported project files, so arbitrary JSON isn't mistaken for a project
+const BUNDLE_FORMAT: &str = "data-contract-creator/project";
+
+/// The contents of a project, restored when it is opened
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Draft {
+    pub document_types: Vec<DocumentType>,
+    pub settings: ContractSettings,
+    pub json_object: Vec<String>,
+    pub error_messages: Vec<String>,
+    pub imported_json: String,
+    pub schema: String,
+    pub history: Vec<HistoryEntry>,
+    pub history_head: Option<usize>,
+    /// Previous version of the contract that update compatibility is checked against
+    pub baseline: Option<Vec<DocumentType>>,
+    /// Settings of the previous version, if it was loaded as a full contract
+    pub baseline_settings: Option<ContractSettings>,
+    /// Identity id that will own the registered contract
+    pub owner_id: String,
+    /// Id and version of the registered contract that updates are built for
+    pub contract_id: String,
+    pub contract_version: String,
+    /// Queries checked by the query planner
+    pub queries: Vec<Query>,
+}
+
+/// A named contract with its own document types and AI history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Project {
+    pub name: String,
+    pub draft: Draft,
+}
+
+impl Project {
+    /// An empty project with one blank document type, like a fresh page load
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            draft: Draft {
+                document_types: vec![DocumentType::default()],
+                ..Default::default()
+            },
+        }
+    }
+}
+
+/// All projects and which one is open
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Workspace {
+    pub projects: Vec<Project>,
+    pub active: usize,
+    /// gRPC-web URL of the DAPI node that contracts are broadcast to
+    #[serde(default)]
+    pub dapi_endpoint: String,
+}
+
+impl Default for Workspace {
+    fn default() -> Self {
+        Self {
+            projects: vec![Project::new(String::from("Project 1"))],
+            active: 0,
+            dapi_endpoint: String::new(),
+        }
+    }
+}
+
+/// A single project exported as a file
+#[derive(Serialize, Deserialize)]
+struct ProjectBundle {
+    format: String,
+    version: u32,
+    project: Project,
+}
+
+fn local_storage() -> Option<web_sys::Storage> {
+    web_sys::window()?.local_storage().ok()?
+}
+
+/// Loads the saved workspace, migrating a draft saved before projects existed.
+/// Returns None if nothing usable is saved.
+pub fn load_workspace() -> Option<Workspace> {
+    let storage = local_storage()?;
+    if let Some(text) = storage.get_item(WORKSPACE_KEY).ok()? {
+        let workspace: Workspace = serde_json::from_str(&text).ok()?;
+        if workspace.projects.is_empty() {
+            return None;
+        }
+        let active = workspace.active.min(workspace.projects.len() - 1);
+        return Some(Workspace { active, ..workspace });
+    }
+    let text = storage.get_item(LEGACY_DRAFT_KEY).ok()??;
+    let draft: Draft = serde_json::from_str(&text).ok()?;
+    let _ = storage.remove_item(LEGACY_DRAFT_KEY);
+    Some(Workspace {
+        projects: vec![Project { name: String::from("Project 1"), draft }],
+        active: 0,
+        dapi_endpoint: String::new(),
+    })
+}
+
+/// Saves the workspace, ignoring failures such as a full or disabled storage
+pub fn save_workspace(workspace: &Workspace) {
+    if let (Some(storage), Ok(text)) = (local_storage(), serde_json::to_string(workspace)) {
+        let _ = storage.set_item(WORKSPACE_KEY, &text);
+    }
+}
+
+/// Serializes a project, including its AI history, into a single pretty-printed JSON file
+pub fn export_project(project: &Project) -> String {
+    let bundle = ProjectBundle {
+        format: String::from(BUNDLE_FORMAT),
+        version: 1,
+        project: project.clone(),
+    };
+    serde_json::to_string_pretty(&bundle).unwrap_or_default()
+}
+
+/// Parses a file created by `export_project`
+pub fn import_project(text: &str) -> Result<Project, anyhow::Error> {
+    let bundle: ProjectBundle = serde_json::from_str(text)
+        .map_err(|e| anyhow::anyhow!("Not a project file: {}", e))?;
+    if bundle.format != BUNDLE_FORMAT {
+        return Err(anyhow::anyhow!("Not a project file: unexpected format \"{}\"", bundle.format));
+    }
+    if bundle.version != 1 {
+        return Err(anyhow::anyhow!("Unsupported project file version {}", bundle.version));
+    }
+    Ok(bundle.project)
+}